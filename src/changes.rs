@@ -0,0 +1,384 @@
+use crate::color::LinguistLanguage;
+use crate::config::Config;
+use crate::graphql::{GitHub, RepositoryStat};
+use crate::renderer::LanguageSummary;
+use crate::AppError;
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use reqwest::{RequestBuilder, StatusCode};
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// Used for languages that linguist gives no color.
+const UNKNOWN_LANGUAGE_COLOR: &str = "#8b949e";
+
+///
+/// A file changed by a commit, as returned by the REST API
+///
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ChangedFile {
+    #[serde(rename = "filename")]
+    pub path: String,
+    pub additions: i64,
+    pub deletions: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct CommitItem {
+    sha: String,
+    parents: Vec<IgnoredAny>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CommitDetail {
+    #[serde(default)]
+    files: Vec<ChangedFile>,
+}
+
+///
+/// The file name and extension rules of linguist. Its heuristics and classifier are not
+/// reproduced, so an extension used by several languages always maps to the same one.
+///
+#[derive(Debug)]
+pub struct LanguageRules {
+    extensions: HashMap<String, String>,
+    filenames: HashMap<String, String>,
+}
+
+impl LanguageRules {
+    ///
+    /// Build the rules from `languages.yml`. An extension claimed by several languages goes to
+    /// the one that lists it first (its primary extension), then to the one with more
+    /// extensions, then to the first name in alphabetical order.
+    ///
+    pub fn new(languages: &HashMap<String, LinguistLanguage>) -> Self {
+        let mut extensions = HashMap::new();
+        let mut filenames = HashMap::new();
+        for (name, language) in languages {
+            for (i, extension) in language.extensions.iter().enumerate() {
+                let rank = (i > 0, Reverse(language.extensions.len()), name.as_str());
+                let entry = extensions.entry(extension.to_lowercase()).or_insert(rank);
+                if rank < *entry {
+                    *entry = rank;
+                }
+            }
+            for filename in &language.filenames {
+                let entry = filenames.entry(filename.clone()).or_insert(name.as_str());
+                if name.as_str() < *entry {
+                    *entry = name.as_str();
+                }
+            }
+        }
+        Self {
+            extensions: extensions
+                .into_iter()
+                .map(|(extension, (_, _, name))| (extension, name.to_string()))
+                .collect(),
+            filenames: filenames
+                .into_iter()
+                .map(|(filename, name)| (filename, name.to_string()))
+                .collect(),
+        }
+    }
+
+    ///
+    /// Find the language of a path by its file name, then by its longest known extension.
+    ///
+    pub fn classify(&self, path: &str) -> Option<&str> {
+        let filename = path.rsplit('/').next()?;
+        if let Some(name) = self.filenames.get(filename) {
+            return Some(name);
+        }
+        let lowercase = filename.to_lowercase();
+        lowercase
+            .match_indices('.')
+            .find_map(|(i, _)| self.extensions.get(&lowercase[i..]))
+            .map(String::as_str)
+    }
+}
+
+///
+/// Count the lines added and deleted by language in the commits of the period `(from, to)`.
+///
+pub async fn get_language_changes(
+    github: &GitHub,
+    stats: &[RepositoryStat],
+    (from, to): (DateTime<Utc>, DateTime<Utc>),
+    languages: &HashMap<String, LinguistLanguage>,
+    language_colors: &HashMap<String, String>,
+    language_groups: &HashMap<String, String>,
+    config: &Config,
+) -> Result<LanguageSummary, AppError> {
+    let mut files = Vec::new();
+    for stat in stats.iter().filter(|s| s.period_commit_count > 0) {
+        let max_commits = config.changes.max_commits;
+        match get_changed_files(github, &stat.name_with_owner, from, to, max_commits).await {
+            Ok(changed) => files.extend(changed),
+            // gone since the statistics were fetched, or empty
+            Err(AppError::RepositoryUnavailableError) => {
+                warn!("skipping the changes of {}", stat.name_with_owner)
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    let rules = LanguageRules::new(languages);
+    Ok(summarize_changes(
+        &files,
+        &rules,
+        language_colors,
+        language_groups,
+        config,
+    ))
+}
+
+///
+/// Aggregate the changed lines of the files by the language of their paths.
+///
+pub fn summarize_changes(
+    files: &[ChangedFile],
+    rules: &LanguageRules,
+    language_colors: &HashMap<String, String>,
+    language_groups: &HashMap<String, String>,
+    config: &Config,
+) -> LanguageSummary {
+    let mut summary = LanguageSummary::new();
+    for file in files {
+        match rules.classify(&file.path) {
+            Some(language) => summary.add(
+                language,
+                UNKNOWN_LANGUAGE_COLOR,
+                file.additions + file.deletions,
+                language_colors,
+                language_groups,
+                config,
+            ),
+            None => debug!("no language for {}", file.path),
+        }
+    }
+    summary
+}
+
+///
+/// Send a request and read its JSON. A repository that is not found (404) or empty (409) is
+/// `RepositoryUnavailableError`, any other error status `ResponseError`.
+///
+async fn get_json<T: serde::de::DeserializeOwned>(request: RequestBuilder) -> Result<T, AppError> {
+    let body_text = request
+        .send()
+        .await
+        .map_err(|_| AppError::RequestError)?
+        .error_for_status()
+        .map_err(|e| match e.status() {
+            Some(StatusCode::NOT_FOUND | StatusCode::CONFLICT) => {
+                AppError::RepositoryUnavailableError
+            }
+            _ => AppError::ResponseError,
+        })?
+        .text()
+        .await
+        .map_err(|_| AppError::ResponseError)?;
+    serde_json::from_str(&body_text).map_err(|_| AppError::JsonDeserializeError)
+}
+
+///
+/// Get the files changed by the commits of the default branch in the period.
+/// Merge commits are skipped because their files were counted with the merged commits.
+///
+async fn get_changed_files(
+    github: &GitHub,
+    name_with_owner: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    max_commits: usize,
+) -> Result<Vec<ChangedFile>, AppError> {
+    const PER_PAGE: usize = 100;
+    let path = format!("/repos/{}/commits", name_with_owner);
+
+    let mut commits = Vec::new();
+    for page in 1.. {
+        let request = github.rest(&path)?.query(&[
+            ("since", from.to_rfc3339()),
+            ("until", to.to_rfc3339()),
+            ("per_page", PER_PAGE.to_string()),
+            ("page", page.to_string()),
+        ]);
+        let items: Vec<CommitItem> = get_json(request).await?;
+        let last_page = items.len() < PER_PAGE;
+        commits.extend(items.into_iter().filter(|c| c.parents.len() <= 1));
+        if last_page || commits.len() >= max_commits {
+            break;
+        }
+    }
+    commits.truncate(max_commits);
+
+    let mut files = Vec::new();
+    for commit in commits {
+        debug!("fetching files of {}@{}", name_with_owner, commit.sha);
+        let detail: CommitDetail =
+            get_json(github.rest(&format!("{}/{}", path, commit.sha))?).await?;
+        files.extend(detail.files);
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_stat;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Request, Response, Server};
+    use std::convert::Infallible;
+
+    fn create_languages() -> HashMap<String, LinguistLanguage> {
+        serde_yaml::from_str(
+            r##"
+C:
+  color: "#555555"
+  extensions: [".c", ".h"]
+C++:
+  color: "#f34b7d"
+  extensions: [".cpp", ".cc", ".h", ".hpp"]
+TypeScript:
+  color: "#3178c6"
+  extensions: [".ts", ".cts", ".mts"]
+Dockerfile:
+  color: "#384d54"
+  extensions: [".dockerfile"]
+  filenames: ["Dockerfile"]
+"##,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_classify() {
+        let rules = LanguageRules::new(&create_languages());
+        assert_eq!(rules.classify("src/main.c"), Some("C"));
+        assert_eq!(rules.classify("src/lib.HPP"), Some("C++"));
+        // listed by both, more extensions wins
+        assert_eq!(rules.classify("include/lib.h"), Some("C++"));
+        assert_eq!(rules.classify("types/index.d.ts"), Some("TypeScript"));
+        assert_eq!(rules.classify("docker/Dockerfile"), Some("Dockerfile"));
+        assert_eq!(rules.classify("README"), None);
+    }
+
+    #[test]
+    fn test_summarize_changes() {
+        let config: Config = toml::from_str(
+            r#"
+            languages_count = 10
+            ignore_repositories = []
+            ignore_languages = ["Dockerfile"]
+            [language_mapping]
+            "C++" = "C"
+            [rename_language]
+            C = "C/C++"
+            "#,
+        )
+        .unwrap();
+        let file = |path: &str, additions, deletions| ChangedFile {
+            path: path.to_string(),
+            additions,
+            deletions,
+        };
+        let files = vec![
+            file("a.c", 10, 5),
+            file("b.cpp", 3, 0),
+            file("c.ts", 1, 1),
+            file("Dockerfile", 7, 7),
+            file("LICENSE", 100, 0),
+        ];
+        let rules = LanguageRules::new(&create_languages());
+        let summary = summarize_changes(&files, &rules, &HashMap::new(), &HashMap::new(), &config);
+        assert_eq!(summary.total_size, 20);
+        assert_eq!(summary.data["C/C++"].size, 18);
+        assert_eq!(summary.data["TypeScript"].size, 2);
+        assert_eq!(summary.data["TypeScript"].color, UNKNOWN_LANGUAGE_COLOR);
+        assert!(!summary.data.contains_key("Dockerfile"));
+    }
+
+    ///
+    /// Answer like the REST API: hello has a commit and a merge, empty is empty, broken fails
+    /// and any other repository is not found.
+    ///
+    async fn answer(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+        assert_eq!(request.headers()["authorization"], "Bearer test-token");
+        let (status, body) = match request.uri().path() {
+            "/repos/octocat/hello/commits" => (
+                StatusCode::OK,
+                r#"[{"sha": "abc", "parents": [{}]}, {"sha": "def", "parents": [{}, {}]}]"#,
+            ),
+            "/repos/octocat/hello/commits/abc" => (
+                StatusCode::OK,
+                r#"{"files": [{"filename": "a.c", "additions": 3, "deletions": 1}]}"#,
+            ),
+            "/repos/octocat/empty/commits" => (
+                StatusCode::CONFLICT,
+                r#"{"message": "Git Repository is empty."}"#,
+            ),
+            "/repos/octocat/broken/commits" => (StatusCode::INTERNAL_SERVER_ERROR, "{}"),
+            _ => (StatusCode::NOT_FOUND, r#"{"message": "Not Found"}"#),
+        };
+        let response = Response::builder().status(status).body(Body::from(body));
+        Ok(response.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_get_language_changes() {
+        let make_service = make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(answer)) });
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let github = GitHub {
+            graphql_url: format!("http://{}/graphql", server.local_addr()),
+            api_url: format!("http://{}", server.local_addr()),
+            token: "test-token".to_string(),
+        };
+        tokio::spawn(server);
+
+        let config: Config = toml::from_str(
+            r#"
+            languages_count = 10
+            ignore_repositories = []
+            ignore_languages = []
+            [language_mapping]
+            [rename_language]
+            "#,
+        )
+        .unwrap();
+        let stat = |name: &str| RepositoryStat {
+            period_commit_count: 1,
+            ..create_stat(name)
+        };
+        let now = Utc::now();
+        let period = (now - chrono::Duration::days(7), now);
+        let languages = create_languages();
+        let colors = HashMap::new();
+        let summary = get_language_changes(
+            &github,
+            &[stat("hello"), stat("empty"), stat("gone")],
+            period,
+            &languages,
+            &colors,
+            &colors,
+            &config,
+        )
+        .await
+        .unwrap();
+        // the merge is skipped, empty and gone are skipped with a warning
+        assert_eq!(summary.total_size, 4);
+        assert_eq!(summary.data["C"].size, 4);
+
+        let result = get_language_changes(
+            &github,
+            &[stat("broken")],
+            period,
+            &languages,
+            &colors,
+            &colors,
+            &config,
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::ResponseError)));
+    }
+}
@@ -3,15 +3,24 @@ use reqwest::get;
 use serde::Deserialize;
 use std::collections::HashMap;
 
+///
+/// One entry of linguist's `languages.yml`
+///
 #[derive(Debug, Deserialize)]
-pub struct LanguageColor {
+pub struct LinguistLanguage {
     pub color: Option<String>,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub filenames: Vec<String>,
+    /// The language this one is shown as on GitHub, e.g. `TSX` is in the `TypeScript` group.
+    pub group: Option<String>,
 }
 
 ///
-/// Retrieve color information for each programming language used in GitHub repositories.
+/// Retrieve the definitions of every language known to GitHub from linguist.
 ///
-pub async fn get_language_color_settings() -> Result<HashMap<String, String>, AppError> {
+pub async fn get_linguist_languages() -> Result<HashMap<String, LinguistLanguage>, AppError> {
     let url = "https://raw.githubusercontent.com/github/linguist/master/lib/linguist/languages.yml";
     let resp = get(url)
         .await
@@ -20,26 +29,86 @@ pub async fn get_language_color_settings() -> Result<HashMap<String, String>, Ap
         .await
         .map_err(|_| AppError::ResponseError)?;
 
-    let language_colors: HashMap<String, LanguageColor> =
-        serde_yaml::from_str(&resp).map_err(|_| AppError::ConvertError)?;
+    serde_yaml::from_str(&resp).map_err(|_| AppError::ConvertError)
+}
 
+///
+/// Retrieve color information for each programming language used in GitHub repositories.
+///
+pub fn get_language_color_settings(
+    languages: &HashMap<String, LinguistLanguage>,
+) -> HashMap<String, String> {
     let mut result: HashMap<String, String> = HashMap::new();
-    for lang in language_colors.keys() {
-        let lang_color = language_colors.get(lang);
-        if let Some(c) = lang_color {
-            if let Some(color_string) = &c.color {
-                result.insert(lang.to_string(), color_string.to_string());
-            }
+    for (lang, settings) in languages {
+        if let Some(color_string) = &settings.color {
+            result.insert(lang.to_string(), color_string.to_string());
         }
     }
-    Ok(result)
+    result
+}
+
+///
+/// Map each language that linguist puts in a group to the name of the group.
+///
+pub fn get_language_group_settings(
+    languages: &HashMap<String, LinguistLanguage>,
+) -> HashMap<String, String> {
+    languages
+        .iter()
+        .filter_map(|(lang, settings)| Some((lang.to_string(), settings.group.clone()?)))
+        .collect()
+}
+
+///
+/// The minimum contrast ratio WCAG 2.x requires for normal text (1.4.3, level AA).
+///
+pub const MIN_TEXT_CONTRAST: f64 = 4.5;
+
+///
+/// The minimum contrast ratio WCAG 2.x requires for graphical objects (1.4.11, level AA).
+///
+pub const MIN_GRAPHIC_CONTRAST: f64 = 3.0;
+
+///
+/// Calculate the WCAG 2.x contrast ratio of two colors given in `#rgb` or `#rrggbb` notation.
+///
+pub fn contrast_ratio(a: &str, b: &str) -> Option<f64> {
+    let a = relative_luminance(a)?;
+    let b = relative_luminance(b)?;
+    let (lighter, darker) = if a > b { (a, b) } else { (b, a) };
+    Some((lighter + 0.05) / (darker + 0.05))
+}
+
+fn relative_luminance(color: &str) -> Option<f64> {
+    let (r, g, b) = parse_hex_color(color)?;
+    let linear = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    Some(0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b))
+}
+
+fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.strip_prefix('#')?;
+    let hex = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect::<String>(),
+        6 => hex.to_string(),
+        _ => return None,
+    };
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
 }
 
 mod tests {
 
     #[tokio::test]
     async fn test_get_language_color_settings() {
-        let colors = crate::color::get_language_color_settings().await.unwrap();
+        let languages = crate::color::get_linguist_languages().await.unwrap();
+        let colors = crate::color::get_language_color_settings(&languages);
         assert_ne!(colors.len(), 0);
 
         assert!(colors.contains_key("Rust"));
@@ -49,6 +118,22 @@ mod tests {
         let rust = colors.get("Rust");
         assert!(rust.is_some());
         let rust_color = rust.unwrap();
-        assert!(rust_color.starts_with("#"));
+        assert!(rust_color.starts_with('#'));
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        use crate::color::contrast_ratio;
+
+        assert_eq!(contrast_ratio("#000", "#fff"), Some(21.0));
+        assert_eq!(contrast_ratio("#ffffff", "#ffffff"), Some(1.0));
+        assert_eq!(
+            contrast_ratio("#2f80ed", "#fff"),
+            contrast_ratio("#fff", "#2f80ed")
+        );
+        let ratio = contrast_ratio("#808080", "#ffffff").unwrap();
+        assert!((ratio - 3.95).abs() < 0.01);
+        assert_eq!(contrast_ratio("red", "#fff"), None);
+        assert_eq!(contrast_ratio("#12345", "#fff"), None);
     }
 }
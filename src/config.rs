@@ -1,17 +1,521 @@
+use crate::envelope;
+use crate::export::Format;
+use crate::filter::{Filter, NamePattern};
+use crate::i18n::Locale;
+use crate::schedule::Cron;
+use crate::AppError;
+use log::warn;
 use serde::Deserialize;
-use std::{collections::HashMap, fs};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 pub struct Config {
     pub languages_count: usize,
     pub ignore_repositories: Vec<String>,
+    /// Applied together with `ignore_repositories` before anything is counted.
+    #[serde(default)]
+    pub filter: Filter,
     pub ignore_languages: Vec<String>,
     pub language_mapping: HashMap<String, String>,
     pub rename_language: HashMap<String, String>,
+    /// Checked in order after `language_mapping`; a language joins the first group it matches.
+    #[serde(default)]
+    pub language_groups: Vec<LanguageGroup>,
+    /// Show languages as the group linguist puts them in, e.g. `TSX` as `TypeScript`.
+    /// Groups of `language_groups` take precedence.
+    #[serde(default)]
+    pub linguist_groups: bool,
+    #[serde(default)]
+    pub language_weight: LanguageWeight,
+    /// Used by the `recency` weight: the sizes of a repository halve every this many days
+    /// since its last push.
+    #[serde(default = "default_language_half_life_days")]
+    pub language_half_life_days: f64,
+    #[serde(default)]
+    pub locale: Locale,
+    #[serde(default)]
+    pub limits: Limits,
+    #[serde(default)]
+    pub theme: Theme,
+    #[serde(default)]
+    pub private_repositories: PrivateRepositories,
+    #[serde(default)]
+    pub output: Output,
+    #[serde(default)]
+    pub history: History,
+    #[serde(default)]
+    pub changes: Changes,
+    /// Project name to topics. The repositories with one of the topics are shown as one bar
+    /// in the commit charts. A repository matching several projects joins the first by name.
+    #[serde(default)]
+    pub projects: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub server: Server,
+    #[serde(default)]
+    pub schedule: Schedule,
+    #[serde(default)]
+    pub publish: Publish,
+    #[serde(default)]
+    pub readme: Readme,
+    /// See `envelope::config_hash`. Set by `try_load`, empty for a config parsed otherwise.
+    #[serde(skip)]
+    pub hash: String,
 }
 
-pub fn load() -> Config {
-    let config_string = fs::read_to_string("config.toml").expect("error: fail to load config.toml");
-    let config_obj: Config = toml::from_str(&config_string).expect("error: fail to create Config");
-    config_obj
+///
+/// Languages that are shown as one bar
+///
+#[derive(Debug, Deserialize, Clone)]
+pub struct LanguageGroup {
+    pub name: String,
+    /// Language names, as globs such as `"*Script"` or regular expressions such as
+    /// `"/^Objective-/"`.
+    pub languages: Vec<NamePattern>,
+    /// Used instead of the linguist color of the group name.
+    pub color: Option<String>,
+}
+
+///
+/// How the size of each language of a repository is counted in the language chart
+///
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LanguageWeight {
+    /// The bytes in the repository, so that the largest repositories dominate.
+    #[default]
+    Size,
+    /// The bytes multiplied by the commits of the last week.
+    Commits,
+    /// The bytes decayed by the time since the last push.
+    Recency,
+}
+
+impl LanguageWeight {
+    ///
+    /// The name of the weight as written in `config.toml`.
+    ///
+    pub fn name(&self) -> &'static str {
+        match self {
+            LanguageWeight::Size => "size",
+            LanguageWeight::Commits => "commits",
+            LanguageWeight::Recency => "recency",
+        }
+    }
+}
+
+fn default_language_half_life_days() -> f64 {
+    30.0
+}
+
+///
+/// The number of bars of each chart. Unset limits fall back to `languages_count`.
+///
+#[derive(Debug, Default, Deserialize, Clone)]
+pub struct Limits {
+    pub languages: Option<usize>,
+    pub commits: Option<usize>,
+    pub active_commits: Option<usize>,
+    pub topics: Option<usize>,
+    /// Add the part left out by the limit as one "Other" bar, so the percentages sum to 100.
+    /// Topics overlap and have no total, so their chart never has it.
+    #[serde(default)]
+    pub other: bool,
+}
+
+impl Config {
+    ///
+    /// Resolve a limit of `[limits]`, e.g. `config.limit(config.limits.commits)`.
+    ///
+    pub fn limit(&self, limit: Option<usize>) -> usize {
+        limit.unwrap_or(self.languages_count)
+    }
+}
+
+///
+/// How each panel treats private repositories that passed the filter
+///
+#[derive(Debug, Deserialize, Clone)]
+pub struct PrivateRepositories {
+    /// The star total. `anonymize` and `aggregate` count the stars like `include`.
+    #[serde(default)]
+    pub header: PrivatePolicy,
+    /// `anonymize` counts the languages like `include`, as no repository name is shown.
+    #[serde(default)]
+    pub languages: PrivatePolicy,
+    #[serde(default = "PrivatePolicy::exclude")]
+    pub commits: PrivatePolicy,
+    #[serde(default = "PrivatePolicy::exclude")]
+    pub active_commits: PrivatePolicy,
+    /// `anonymize` and `aggregate` count the repositories like `include`.
+    #[serde(default = "PrivatePolicy::exclude")]
+    pub topics: PrivatePolicy,
+}
+
+impl Default for PrivateRepositories {
+    fn default() -> Self {
+        Self {
+            header: PrivatePolicy::Include,
+            languages: PrivatePolicy::Include,
+            commits: PrivatePolicy::Exclude,
+            active_commits: PrivatePolicy::Exclude,
+            topics: PrivatePolicy::Exclude,
+        }
+    }
+}
+
+///
+/// How a private repository appears in a panel. Percentages are always taken over what is shown.
+///
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivatePolicy {
+    /// Show it like any other repository.
+    #[default]
+    Include,
+    /// Show it as "private repo #n".
+    Anonymize,
+    /// Add all private repositories up into one "Private" bar.
+    Aggregate,
+    /// Leave it out, also from the totals.
+    Exclude,
+}
+
+impl PrivatePolicy {
+    fn exclude() -> Self {
+        PrivatePolicy::Exclude
+    }
+}
+
+///
+/// Settings that control how the charts are drawn
+///
+#[derive(Debug, Default, Deserialize, Clone)]
+pub struct Theme {
+    #[serde(default)]
+    pub label_overflow: LabelOverflow,
+    /// Warn when a language color is hard to tell apart from the bar track.
+    #[serde(default)]
+    pub check_contrast: bool,
+}
+
+///
+/// How a bar label that is wider than the chart is fitted
+///
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelOverflow {
+    /// Cut the name and append an ellipsis. The full label is kept as a tooltip.
+    #[default]
+    Truncate,
+    /// Reduce the font size until the label fits.
+    Shrink,
+}
+
+///
+/// Settings for the files written by a run
+///
+#[derive(Debug, Deserialize, Clone)]
+pub struct Output {
+    /// Also write the card as PNG for places that cannot show SVG.
+    #[serde(default)]
+    pub png: bool,
+    #[serde(default = "default_png_scale")]
+    pub png_scale: f32,
+    /// Font files used for the characters that the embedded DejaVu Sans lacks, such as a CJK
+    /// font for `locale = "ja"`.
+    #[serde(default)]
+    pub png_fonts: Vec<String>,
+    /// Also write every panel of the card as its own image.
+    #[serde(default)]
+    pub separate_cards: bool,
+    /// Also write the star history card, drawn from the timestamps of the stars. They are only
+    /// fetched when it is set.
+    #[serde(default)]
+    pub star_history: bool,
+    /// Keep the timestamps of the stars in `github_stats.json`, the history and `/stats.json`.
+    #[serde(default)]
+    pub starred_at: bool,
+    /// Also write the numbers of the card in these formats.
+    #[serde(default)]
+    pub exports: Vec<Format>,
+    /// Also write the statistics in the OpenMetrics text format, e.g. for the textfile
+    /// collector of the node exporter.
+    #[serde(default)]
+    pub metrics: bool,
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Self {
+            png: false,
+            png_scale: default_png_scale(),
+            png_fonts: Vec::new(),
+            separate_cards: false,
+            star_history: false,
+            starred_at: false,
+            exports: Vec::new(),
+            metrics: false,
+        }
+    }
+}
+
+fn default_png_scale() -> f32 {
+    2.0
+}
+
+///
+/// Settings for the store that keeps the statistics of every run
+///
+#[derive(Debug, Deserialize, Clone)]
+pub struct History {
+    /// Append each run to the store and draw the trend charts from it.
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_history_path")]
+    pub path: String,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: default_history_path(),
+        }
+    }
+}
+
+fn default_history_path() -> String {
+    "github_stats_history.jsonl".to_string()
+}
+
+///
+/// Settings for the lines changed by language, counted from the files of recent commits
+///
+#[derive(Debug, Deserialize, Clone)]
+pub struct Changes {
+    /// Fetch the changed files of the commits in the period and draw them by language.
+    #[serde(default)]
+    pub enabled: bool,
+    /// The most commits read per repository. Each commit costs one API request.
+    #[serde(default = "default_changes_max_commits")]
+    pub max_commits: usize,
+}
+
+impl Default for Changes {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_commits: default_changes_max_commits(),
+        }
+    }
+}
+
+fn default_changes_max_commits() -> usize {
+    100
+}
+
+///
+/// Settings for the `serve` subcommand
+///
+#[derive(Debug, Deserialize, Clone)]
+pub struct Server {
+    #[serde(default = "default_server_port")]
+    pub port: u16,
+    /// How long fetched statistics are served before they are fetched again. Also used as the
+    /// `max-age` of the responses.
+    #[serde(default = "default_server_refresh_minutes")]
+    pub refresh_minutes: i64,
+    /// The longest `period` a request may ask for.
+    #[serde(default = "default_server_max_period_days")]
+    pub max_period_days: i64,
+    /// The logins served under `/{login}/`, compared case-insensitively. The owner of the token
+    /// is always served at the top level.
+    #[serde(default)]
+    pub users: Vec<String>,
+    /// How many times a minute requests may fetch the statistics of the same user from GitHub.
+    /// Beyond it the stale statistics are served, or 429 when there are none.
+    #[serde(default = "default_server_max_fetches_per_minute")]
+    pub max_fetches_per_minute: u32,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self {
+            port: default_server_port(),
+            refresh_minutes: default_server_refresh_minutes(),
+            max_period_days: default_server_max_period_days(),
+            users: Vec::new(),
+            max_fetches_per_minute: default_server_max_fetches_per_minute(),
+        }
+    }
+}
+
+fn default_server_port() -> u16 {
+    8080
+}
+
+fn default_server_refresh_minutes() -> i64 {
+    60
+}
+
+fn default_server_max_period_days() -> i64 {
+    365
+}
+
+fn default_server_max_fetches_per_minute() -> u32 {
+    10
+}
+
+///
+/// Settings for the `watch` subcommand
+///
+#[derive(Debug, Deserialize, Clone)]
+pub struct Schedule {
+    /// When to generate the cards again, as a cron expression in UTC.
+    #[serde(default = "default_schedule_cron")]
+    pub cron: Cron,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            cron: default_schedule_cron(),
+        }
+    }
+}
+
+fn default_schedule_cron() -> Cron {
+    Cron::try_from("0 * * * *".to_string()).unwrap()
+}
+
+///
+/// Settings for committing the generated files into a git working tree, such as a clone of
+/// the profile repository
+///
+#[derive(Debug, Deserialize, Clone)]
+pub struct Publish {
+    #[serde(default)]
+    pub enabled: bool,
+    /// The working tree, which must already be a git repository.
+    #[serde(default)]
+    pub repository: String,
+    #[serde(default = "default_publish_branch")]
+    pub branch: String,
+    /// The directory of the working tree the files are copied to, the top when empty.
+    #[serde(default)]
+    pub path: String,
+    /// The generated files to publish, as globs of file names.
+    #[serde(default = "default_publish_files")]
+    pub files: Vec<NamePattern>,
+    /// `{date}` is replaced with the time of the run.
+    #[serde(default = "default_publish_message")]
+    pub message: String,
+    /// Used for the author and the committer. Left to the git config when unset.
+    pub author_name: Option<String>,
+    pub author_email: Option<String>,
+    /// The remote the branch is pushed to after a commit. Nothing is pushed when unset.
+    pub remote: Option<String>,
+}
+
+impl Default for Publish {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            repository: String::new(),
+            branch: default_publish_branch(),
+            path: String::new(),
+            files: default_publish_files(),
+            message: default_publish_message(),
+            author_name: None,
+            author_email: None,
+            remote: None,
+        }
+    }
+}
+
+fn default_publish_branch() -> String {
+    "main".to_string()
+}
+
+fn default_publish_files() -> Vec<NamePattern> {
+    [
+        "github_stats*.svg",
+        "github_stats*.png",
+        "github_stats.json",
+    ]
+    .into_iter()
+    .map(|p| NamePattern::try_from(p.to_string()).unwrap())
+    .collect()
+}
+
+fn default_publish_message() -> String {
+    "Update GitHub stats ({date})".to_string()
+}
+
+///
+/// Settings for rewriting the sections of a Markdown file between
+/// `<!-- {marker}:START -->` and `<!-- {marker}:END -->`
+///
+#[derive(Debug, Deserialize, Clone)]
+pub struct Readme {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_readme_path")]
+    pub path: String,
+    /// The section filled with tables of the top languages and repositories.
+    #[serde(default = "default_readme_table_marker")]
+    pub table_marker: String,
+    /// The section filled with an `<img>` tag of `image`.
+    #[serde(default = "default_readme_image_marker")]
+    pub image_marker: String,
+    #[serde(default = "default_readme_image")]
+    pub image: String,
+}
+
+impl Default for Readme {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: default_readme_path(),
+            table_marker: default_readme_table_marker(),
+            image_marker: default_readme_image_marker(),
+            image: default_readme_image(),
+        }
+    }
+}
+
+fn default_readme_path() -> String {
+    "README.md".to_string()
+}
+
+fn default_readme_table_marker() -> String {
+    "STATS".to_string()
+}
+
+fn default_readme_image_marker() -> String {
+    "STATS_IMAGE".to_string()
+}
+
+fn default_readme_image() -> String {
+    "github_stats.svg".to_string()
+}
+
+///
+/// Read `config.toml`. A missing or invalid file is an error instead of a panic, e.g. for a
+/// long-running process that should survive a broken edit.
+///
+pub fn try_load() -> Result<Config, AppError> {
+    let config_string = fs::read_to_string("config.toml").map_err(|e| {
+        warn!("failed to read config.toml: {}", e);
+        AppError::ConfigError
+    })?;
+    let mut config: Config = toml::from_str(&config_string).map_err(|e| {
+        warn!("invalid config.toml: {}", e);
+        AppError::ConfigError
+    })?;
+    config.hash = envelope::config_hash(config_string.as_bytes());
+    Ok(config)
 }
@@ -0,0 +1,363 @@
+use crate::config::Config;
+use crate::envelope;
+use crate::graphql::RepositoryStat;
+use crate::i18n::{self, Locale};
+use crate::renderer::{Panel, Renderer};
+use crate::AppError;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+///
+/// What changed between two snapshots
+///
+#[derive(Serialize, Debug, PartialEq)]
+pub struct DiffReport {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub archived: Vec<String>,
+    pub stars: Vec<Delta>,
+    pub commits: Vec<Delta>,
+    pub languages_gained: Vec<String>,
+    pub languages_lost: Vec<String>,
+    pub ranks: Vec<RankChange>,
+}
+
+///
+/// The change of a count of a repository that exists in both snapshots
+///
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Delta {
+    pub name: String,
+    pub before: i64,
+    pub after: i64,
+    pub delta: i64,
+}
+
+///
+/// A move in one of the top-N charts. Ranks start at 1, `None` means not shown.
+///
+#[derive(Serialize, Debug, PartialEq)]
+pub struct RankChange {
+    pub chart: String,
+    pub name: String,
+    pub before: Option<usize>,
+    pub after: Option<usize>,
+}
+
+///
+/// Load a snapshot written as `github_stats.json`.
+///
+pub fn load_stats(path: &Path) -> Result<Vec<RepositoryStat>, AppError> {
+    let content = std::fs::read_to_string(path).map_err(|_| AppError::DiffError)?;
+    envelope::parse_repositories(&content)
+}
+
+///
+/// Compare two snapshots. Ranks are computed by `Renderer` with `config` so that they match
+/// the charts, as of `now` for the recency weighting.
+///
+pub fn diff(
+    before: Vec<RepositoryStat>,
+    after: Vec<RepositoryStat>,
+    config: &Config,
+    now: DateTime<Utc>,
+) -> DiffReport {
+    let old: HashMap<_, _> = before.iter().map(|r| (r.id.as_str(), r)).collect();
+    let new: HashMap<_, _> = after.iter().map(|r| (r.id.as_str(), r)).collect();
+
+    let added = after
+        .iter()
+        .filter(|r| !old.contains_key(r.id.as_str()))
+        .map(|r| r.name.clone())
+        .collect();
+    let removed = before
+        .iter()
+        .filter(|r| !new.contains_key(r.id.as_str()))
+        .map(|r| r.name.clone())
+        .collect();
+    let archived = after
+        .iter()
+        .filter(|r| r.is_archived && old.get(r.id.as_str()).map_or(false, |o| !o.is_archived))
+        .map(|r| r.name.clone())
+        .collect();
+    let delta = |count: fn(&RepositoryStat) -> i64| {
+        after
+            .iter()
+            .filter_map(|r| {
+                let o = old.get(r.id.as_str())?;
+                let delta = count(r) - count(o);
+                (delta != 0).then(|| Delta {
+                    name: r.name.clone(),
+                    before: count(o),
+                    after: count(r),
+                    delta,
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+    let stars = delta(|r| r.stargazer_count);
+    let commits = delta(|r| r.total_commit_count);
+
+    let renderer = |stats| {
+        Renderer::with_config(
+            stats,
+            HashMap::new(),
+            HashMap::new(),
+            config.clone(),
+            (now - Duration::days(crate::PERIOD_DAYS), now),
+        )
+    };
+    let before = renderer(before);
+    let after = renderer(after);
+    let old_languages: BTreeSet<_> = before.language_summary.data.keys().collect();
+    let new_languages: BTreeSet<_> = after.language_summary.data.keys().collect();
+    let languages_gained = new_languages
+        .difference(&old_languages)
+        .map(|l| l.to_string())
+        .collect();
+    let languages_lost = old_languages
+        .difference(&new_languages)
+        .map(|l| l.to_string())
+        .collect();
+
+    let count = config.limit(config.limits.languages);
+    let mut ranks = Vec::new();
+    ranks.extend(rank_changes(
+        Panel::Languages,
+        before.top_languages(count).iter().map(|d| d.name.as_str()),
+        after.top_languages(count).iter().map(|d| d.name.as_str()),
+    ));
+    let count = config.limit(config.limits.commits);
+    ranks.extend(rank_changes(
+        Panel::Commits,
+        before
+            .top_commit_repositories(count)
+            .iter()
+            .map(|r| r.name.as_str()),
+        after
+            .top_commit_repositories(count)
+            .iter()
+            .map(|r| r.name.as_str()),
+    ));
+    let count = config.limit(config.limits.active_commits);
+    ranks.extend(rank_changes(
+        Panel::ActiveCommits,
+        before
+            .top_active_commit_repositories(count)
+            .iter()
+            .map(|r| r.name.as_str()),
+        after
+            .top_active_commit_repositories(count)
+            .iter()
+            .map(|r| r.name.as_str()),
+    ));
+
+    DiffReport {
+        added,
+        removed,
+        archived,
+        stars,
+        commits,
+        languages_gained,
+        languages_lost,
+        ranks,
+    }
+}
+
+fn rank_changes<'a>(
+    panel: Panel,
+    before: impl Iterator<Item = &'a str>,
+    after: impl Iterator<Item = &'a str>,
+) -> Vec<RankChange> {
+    let before: Vec<_> = before.collect();
+    let after: Vec<_> = after.collect();
+    let rank = |list: &[&str], name: &str| list.iter().position(|n| *n == name).map(|i| i + 1);
+
+    let mut changes: Vec<_> = after
+        .iter()
+        .map(|name| (*name, rank(&before, name), rank(&after, name)))
+        .chain(
+            before
+                .iter()
+                .filter(|name| !after.contains(name))
+                .map(|name| (*name, rank(&before, name), None)),
+        )
+        .filter(|(_, before, after)| before != after)
+        .map(|(name, before, after)| RankChange {
+            chart: panel.name().to_string(),
+            name: name.to_string(),
+            before,
+            after,
+        })
+        .collect();
+    changes.sort_by_key(|c| c.after.unwrap_or(usize::MAX));
+    changes
+}
+
+impl DiffReport {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.archived.is_empty()
+            && self.stars.is_empty()
+            && self.commits.is_empty()
+            && self.languages_gained.is_empty()
+            && self.languages_lost.is_empty()
+            && self.ranks.is_empty()
+    }
+
+    ///
+    /// Describe every change in one line each, in the language of `locale`.
+    ///
+    pub fn lines(&self, locale: Locale) -> Vec<String> {
+        let messages = locale.messages();
+        if self.is_empty() {
+            return vec![messages.diff_no_changes.to_string()];
+        }
+        let signed = |n: i64| {
+            let sign = if n < 0 { "-" } else { "+" };
+            format!("{}{}", sign, locale.format_number(n.abs()))
+        };
+        let rank = |r: Option<usize>| r.map_or("-".to_string(), |r| format!("#{}", r));
+
+        let mut lines = Vec::new();
+        for name in &self.added {
+            lines.push(i18n::fill(messages.diff_added, &[("name", name)]));
+        }
+        for name in &self.removed {
+            lines.push(i18n::fill(messages.diff_removed, &[("name", name)]));
+        }
+        for name in &self.archived {
+            lines.push(i18n::fill(messages.diff_archived, &[("name", name)]));
+        }
+        for (deltas, message) in [
+            (&self.stars, messages.diff_stars),
+            (&self.commits, messages.diff_commits),
+        ] {
+            for d in deltas {
+                lines.push(i18n::fill(
+                    message,
+                    &[
+                        ("name", &d.name),
+                        ("delta", &signed(d.delta)),
+                        ("after", &locale.format_number(d.after)),
+                    ],
+                ));
+            }
+        }
+        for name in &self.languages_gained {
+            lines.push(i18n::fill(messages.diff_language_gained, &[("name", name)]));
+        }
+        for name in &self.languages_lost {
+            lines.push(i18n::fill(messages.diff_language_lost, &[("name", name)]));
+        }
+        for r in &self.ranks {
+            let chart = match r.chart.as_str() {
+                "languages" => messages.top_languages.to_string(),
+                "commits" => messages.top_commits.to_string(),
+                // the snapshots are those of the generated cards
+                _ => locale.fill_period(messages.top_active_commits, crate::PERIOD_DAYS),
+            };
+            lines.push(i18n::fill(
+                messages.diff_rank,
+                &[
+                    ("chart", &chart),
+                    ("name", &r.name),
+                    ("before", &rank(r.before)),
+                    ("after", &rank(r.after)),
+                ],
+            ));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, create_config, create_language, create_now};
+
+    fn create_stat(name: &str, stars: i64, commits: i64, languages: &[&str]) -> RepositoryStat {
+        RepositoryStat {
+            disk_usage: 100,
+            stargazer_count: stars,
+            languages: languages
+                .iter()
+                .map(|l| create_language(l, "#000000", 1000))
+                .collect(),
+            total_commit_count: commits,
+            ..test_support::create_stat(name)
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = vec![
+            create_stat("foo", 1, 10, &["Rust"]),
+            create_stat("bar", 2, 20, &["Go"]),
+            create_stat("baz", 0, 5, &["Go"]),
+        ];
+        let mut archived = create_stat("baz", 0, 5, &["Go"]);
+        archived.is_archived = true;
+        let after = vec![
+            create_stat("foo", 4, 30, &["Rust"]),
+            archived,
+            create_stat("qux", 0, 1, &["Zig"]),
+        ];
+
+        let report = diff(before, after, &create_config(), create_now());
+        assert_eq!(report.added, vec!["qux"]);
+        assert_eq!(report.removed, vec!["bar"]);
+        assert_eq!(report.archived, vec!["baz"]);
+        assert_eq!(
+            report.stars,
+            vec![Delta {
+                name: "foo".to_string(),
+                before: 1,
+                after: 4,
+                delta: 3
+            }]
+        );
+        assert_eq!(report.commits.len(), 1);
+        assert_eq!(report.languages_gained, vec!["Zig"]);
+        assert_eq!(report.languages_lost, Vec::<String>::new());
+
+        let commits: Vec<_> = report
+            .ranks
+            .iter()
+            .filter(|r| r.chart == "commits")
+            .collect();
+        assert_eq!(commits.len(), 4);
+        assert_eq!(
+            (
+                commits[0].name.as_str(),
+                commits[0].before,
+                commits[0].after
+            ),
+            ("foo", Some(2), Some(1))
+        );
+        assert_eq!(
+            (
+                commits[3].name.as_str(),
+                commits[3].before,
+                commits[3].after
+            ),
+            ("bar", Some(1), None)
+        );
+
+        let lines = report.lines(Locale::En);
+        assert!(lines.contains(&"New repository: qux".to_string()));
+        assert!(lines.contains(&"foo: +3 stars (4)".to_string()));
+        assert!(lines.contains(&"Top Commits: foo #2 → #1".to_string()));
+    }
+
+    #[test]
+    fn test_diff_no_changes() {
+        let stats = vec![create_stat("foo", 1, 10, &["Rust"])];
+        let report = diff(stats.clone(), stats, &create_config(), create_now());
+        assert!(report.is_empty());
+        assert_eq!(report.lines(Locale::En), vec!["No changes"]);
+    }
+}
@@ -0,0 +1,219 @@
+//! The versioned `github_stats.json`. Its JSON Schema in `schema/github_stats.schema.json`
+//! is maintained by hand next to the serde types here and in `graphql`. The tests validate
+//! an envelope serialized with every field against it, so a field the schema lacks fails them.
+
+use crate::graphql::RepositoryStat;
+use crate::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Incremented when a field is removed or changes its meaning. Adding a field keeps it.
+/// 1 always had `starred_at` in each repository; since 2 it is only written when asked for.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// The JSON Schema of `github_stats.json`, see the module documentation.
+pub const JSON_SCHEMA: &str = include_str!("../schema/github_stats.schema.json");
+
+///
+/// The contents of `github_stats.json`: the statistics with what they were counted from
+///
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Envelope {
+    pub schema_version: u32,
+    pub generated_at: DateTime<Utc>,
+    /// The period of `period_commit_count`.
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub login: String,
+    /// See `config_hash`.
+    pub config_hash: String,
+    pub repositories: Vec<RepositoryStat>,
+}
+
+impl Envelope {
+    pub fn new(
+        login: String,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        repositories: Vec<RepositoryStat>,
+        config_hash: String,
+    ) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            generated_at: Utc::now(),
+            from,
+            to,
+            login,
+            config_hash,
+            repositories,
+        }
+    }
+}
+
+///
+/// The FNV-1a hash of the contents of `config.toml` as 16 hex digits, stable across builds so
+/// that files of different runs can be compared.
+///
+pub fn config_hash(config: &[u8]) -> String {
+    let hash = config.iter().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+///
+/// Read the repositories of a `github_stats.json`. Files written before the envelope, which
+/// are a bare array, are also accepted; files of a newer schema version are not.
+///
+pub fn parse_repositories(content: &str) -> Result<Vec<RepositoryStat>, AppError> {
+    if content.trim_start().starts_with('[') {
+        return serde_json::from_str(content).map_err(|_| AppError::JsonDeserializeError);
+    }
+    let envelope: Envelope =
+        serde_json::from_str(content).map_err(|_| AppError::JsonDeserializeError)?;
+    if envelope.schema_version > SCHEMA_VERSION {
+        return Err(AppError::JsonDeserializeError);
+    }
+    Ok(envelope.repositories)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_language, create_stat};
+    use serde_json::Value;
+
+    fn create_envelope() -> Envelope {
+        let time = "2023-08-08T00:00:00Z".parse().unwrap();
+        // every field, also the optional starred_at, for the schema to check
+        let stat = RepositoryStat {
+            id: "R_1".to_string(),
+            starred_at: vec![time],
+            languages: vec![create_language("Rust", "#dea584", 100)],
+            ..create_stat("foo")
+        };
+        Envelope::new(
+            "octocat".to_string(),
+            time,
+            time,
+            vec![stat],
+            config_hash(b"languages_count = 10"),
+        )
+    }
+
+    ///
+    /// Check `value` against `schema`, with the keywords `JSON_SCHEMA` uses. Unlike JSON
+    /// Schema, a property the schema does not declare is an error, so that a field added to
+    /// `Envelope` fails until the schema has it too.
+    ///
+    fn validate(value: &Value, schema: &Value, root: &Value, path: &str) -> Result<(), String> {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let target = reference
+                .strip_prefix('#')
+                .and_then(|pointer| root.pointer(pointer))
+                .ok_or_else(|| format!("{}: unknown $ref {}", path, reference))?;
+            return validate(value, target, root, path);
+        }
+        if let Some(expected) = schema.get("const") {
+            if value != expected {
+                return Err(format!("{}: {} is not {}", path, value, expected));
+            }
+        }
+        if let Some(expected) = schema["type"].as_str() {
+            let matches = match expected {
+                "object" => value.is_object(),
+                "array" => value.is_array(),
+                "string" => value.is_string(),
+                "integer" => value.is_i64() || value.is_u64(),
+                "boolean" => value.is_boolean(),
+                _ => return Err(format!("{}: unknown type {}", path, expected)),
+            };
+            if !matches {
+                return Err(format!("{}: {} is not of type {}", path, value, expected));
+            }
+        }
+        if let Some(minimum) = schema["minimum"].as_i64() {
+            if value.as_i64().map_or(true, |v| v < minimum) {
+                return Err(format!("{}: {} is less than {}", path, value, minimum));
+            }
+        }
+        if let Some(pattern) = schema["pattern"].as_str() {
+            let pattern = regex::Regex::new(pattern).map_err(|e| e.to_string())?;
+            if !pattern.is_match(value.as_str().unwrap_or_default()) {
+                return Err(format!("{}: {} does not match {}", path, value, pattern));
+            }
+        }
+        if schema["format"] == "date-time"
+            && DateTime::parse_from_rfc3339(value.as_str().unwrap_or_default()).is_err()
+        {
+            return Err(format!("{}: {} is not a date-time", path, value));
+        }
+        if let Some(object) = value.as_object() {
+            for name in schema["required"].as_array().into_iter().flatten() {
+                let name = name.as_str().unwrap_or_default();
+                if !object.contains_key(name) {
+                    return Err(format!("{}: {} is missing", path, name));
+                }
+            }
+            for (name, property) in object {
+                let path = format!("{}/{}", path, name);
+                match schema["properties"].get(name) {
+                    Some(schema) => validate(property, schema, root, &path)?,
+                    None => return Err(format!("{}: not in the schema", path)),
+                }
+            }
+        }
+        if let (Some(items), Some(schema)) = (value.as_array(), schema.get("items")) {
+            for (i, item) in items.iter().enumerate() {
+                validate(item, schema, root, &format!("{}/{}", path, i))?;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_schema() {
+        let schema: Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        let validate = |value: &Value| validate(value, &schema, &schema, "");
+        let envelope = serde_json::to_value(create_envelope()).unwrap();
+        assert_eq!(validate(&envelope), Ok(()));
+
+        let mut missing = envelope.clone();
+        missing["repositories"][0]
+            .as_object_mut()
+            .unwrap()
+            .remove("topics");
+        assert_eq!(
+            validate(&missing),
+            Err("/repositories/0: topics is missing".to_string())
+        );
+        let mut added = envelope.clone();
+        added["repositories"][0]["languages"][0]["bytes"] = Value::from(1);
+        assert_eq!(
+            validate(&added),
+            Err("/repositories/0/languages/0/bytes: not in the schema".to_string())
+        );
+        let mut wrong = envelope.clone();
+        wrong["config_hash"] = Value::from("config.toml");
+        assert!(validate(&wrong).is_err());
+        let mut wrong = envelope;
+        wrong["schema_version"] = Value::from(SCHEMA_VERSION + 1);
+        assert!(validate(&wrong).is_err());
+    }
+
+    #[test]
+    fn test_parse_repositories() {
+        let envelope = create_envelope();
+        let json = serde_json::to_string(&envelope).unwrap();
+        assert_eq!(parse_repositories(&json).unwrap(), envelope.repositories);
+        let bare = serde_json::to_string(&envelope.repositories).unwrap();
+        assert_eq!(parse_repositories(&bare).unwrap(), envelope.repositories);
+
+        let newer = Envelope {
+            schema_version: SCHEMA_VERSION + 1,
+            ..envelope
+        };
+        let json = serde_json::to_string(&newer).unwrap();
+        assert!(parse_repositories(&json).is_err());
+    }
+}
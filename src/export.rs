@@ -0,0 +1,478 @@
+use crate::i18n::{self, Locale};
+use crate::renderer::{self, LanguageSummaryValue, Renderer, RepositoryRow, RowKind};
+use serde::Deserialize;
+
+///
+/// A file format the numbers of the card can be exported to
+///
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// The charts as Markdown tables.
+    Markdown,
+    /// Every language and every repository, as two files.
+    Csv,
+    /// The charts as a page that needs no other file.
+    Html,
+}
+
+impl Format {
+    ///
+    /// The files of the format as pairs of file name and contents.
+    ///
+    pub fn export(&self, report: &Report) -> Vec<(&'static str, String)> {
+        match self {
+            Format::Markdown => vec![("github_stats.md", markdown(report))],
+            Format::Csv => vec![
+                ("github_stats_languages.csv", languages_csv(report)),
+                ("github_stats_repositories.csv", repositories_csv(report)),
+            ],
+            Format::Html => vec![("github_stats.html", html(report))],
+        }
+    }
+}
+
+///
+/// One bar of a chart
+///
+#[derive(Debug, PartialEq)]
+pub struct Share {
+    pub name: String,
+    pub color: String,
+    pub percentage: f64,
+    /// The percentage and count as the card writes them, e.g. `45.0% (1,234)`.
+    pub detail: String,
+}
+
+///
+/// A chart as its title and bars
+///
+#[derive(Debug)]
+pub struct Chart {
+    pub title: String,
+    pub shares: Vec<Share>,
+}
+
+///
+/// The numbers of a renderer, taken once so that every format shows the same
+///
+#[derive(Debug)]
+pub struct Report {
+    pub locale: Locale,
+    pub star_count: i64,
+    /// The language chart and the two commit charts, with the bars of the card.
+    pub charts: Vec<Chart>,
+    /// Every counted language, largest first.
+    pub languages: Vec<LanguageSummaryValue>,
+    pub total_language_size: i64,
+    /// Every row of the commit charts, most commits first.
+    pub repositories: Vec<RepositoryRow>,
+}
+
+impl Report {
+    pub fn new(renderer: &Renderer) -> Self {
+        let config = &renderer.config;
+        let locale = config.locale;
+        let messages = locale.messages();
+        let percentage = |count: i64, total: i64| count as f64 / total.max(1) as f64 * 100.0;
+        let language_total = renderer.language_summary.total_size;
+        let languages = renderer
+            .shown_languages()
+            .into_iter()
+            .map(|d| {
+                let percentage = percentage(d.size, language_total);
+                Share {
+                    detail: format!("{}%", locale.format_decimal(percentage, 1)),
+                    name: d.name,
+                    color: d.color,
+                    percentage,
+                }
+            })
+            .collect();
+        let repositories = |rows: Vec<RepositoryRow>, count: fn(&RepositoryRow) -> i64, total| {
+            rows.into_iter()
+                .map(|r| {
+                    let percentage = percentage(count(&r), total);
+                    Share {
+                        detail: format!(
+                            "{}% ({})",
+                            locale.format_decimal(percentage, 1),
+                            locale.format_number(count(&r))
+                        ),
+                        name: r.name,
+                        color: renderer::COMMITS_TREND_COLOR.to_string(),
+                        percentage,
+                    }
+                })
+                .collect()
+        };
+        let summary = &renderer.repository_summary;
+        let charts = vec![
+            Chart {
+                title: renderer::languages_title(config).to_string(),
+                shares: languages,
+            },
+            Chart {
+                title: messages.top_commits.to_string(),
+                shares: repositories(
+                    renderer.shown_commit_repositories(),
+                    |r| r.total_commit_count,
+                    summary.total_commit_count,
+                ),
+            },
+            Chart {
+                title: locale.fill_period(messages.top_active_commits, renderer.period_days()),
+                shares: repositories(
+                    renderer.shown_active_commit_repositories(),
+                    |r| r.period_commit_count,
+                    summary.total_active_commit_count,
+                ),
+            },
+        ];
+
+        let mut all_languages: Vec<_> = renderer.language_summary.data.values().cloned().collect();
+        all_languages.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        let mut all_repositories = renderer.population(config.private_repositories.commits);
+        all_repositories.sort_by(|a, b| {
+            b.total_commit_count
+                .cmp(&a.total_commit_count)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Self {
+            locale,
+            star_count: summary.star_count,
+            charts,
+            languages: all_languages,
+            total_language_size: language_total,
+            repositories: all_repositories,
+        }
+    }
+}
+
+///
+/// A Markdown table with the title of a chart over its names and their shares.
+///
+fn markdown_table(chart: &Chart) -> String {
+    // names end up in a README, where HTML tags and code spans would be rendered
+    let escape = |cell: &str| escape_html(cell).replace('|', "\\|").replace('`', "\\`");
+    let mut lines = vec![
+        format!("| {} | % |", escape(&chart.title)),
+        "| --- | ---: |".to_string(),
+    ];
+    lines.extend(
+        chart
+            .shares
+            .iter()
+            .map(|s| format!("| {} | {} |", escape(&s.name), escape(&s.detail))),
+    );
+    lines.join("\n")
+}
+
+///
+/// Every chart of the report as a Markdown table.
+///
+pub fn markdown_tables(report: &Report) -> String {
+    report
+        .charts
+        .iter()
+        .map(markdown_table)
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn total_stars(report: &Report) -> String {
+    let count = report.locale.format_number(report.star_count);
+    i18n::fill(report.locale.messages().total_stars, &[("count", &count)])
+}
+
+fn markdown(report: &Report) -> String {
+    let messages = report.locale.messages();
+    format!(
+        "# {}\n\n{}\n\n{}\n",
+        messages.card_title,
+        total_stars(report),
+        markdown_tables(report)
+    )
+}
+
+///
+/// Quote a CSV field when it has a separator, a quote or a line break.
+///
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv(header: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut lines = vec![header.join(",")];
+    lines.extend(rows.into_iter().map(|row| {
+        row.iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<_>>()
+            .join(",")
+    }));
+    lines.join("\n") + "\n"
+}
+
+fn languages_csv(report: &Report) -> String {
+    let rows = report
+        .languages
+        .iter()
+        .map(|l| {
+            let percentage = l.size as f64 / report.total_language_size.max(1) as f64 * 100.0;
+            vec![
+                l.name.clone(),
+                l.color.clone(),
+                l.size.to_string(),
+                format!("{:.2}", percentage),
+            ]
+        })
+        .collect();
+    csv(&["name", "color", "size", "percentage"], rows)
+}
+
+fn repositories_csv(report: &Report) -> String {
+    let rows = report
+        .repositories
+        .iter()
+        .map(|r| {
+            // only a row of one repository has flags and topics, the others leave them empty
+            let (private, fork, archived, topics) = match &r.kind {
+                RowKind::Repository(s) => (
+                    s.is_private.to_string(),
+                    s.is_fork.to_string(),
+                    s.is_archived.to_string(),
+                    s.topics.join(" "),
+                ),
+                RowKind::Anonymized | RowKind::Private => (
+                    true.to_string(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                ),
+                RowKind::Project | RowKind::Other => Default::default(),
+            };
+            vec![
+                r.name.clone(),
+                private,
+                fork,
+                archived,
+                r.stargazer_count.to_string(),
+                r.total_commit_count.to_string(),
+                r.period_commit_count.to_string(),
+                topics,
+            ]
+        })
+        .collect();
+    csv(
+        &[
+            "name",
+            "private",
+            "fork",
+            "archived",
+            "stars",
+            "total_commits",
+            "period_commits",
+            "topics",
+        ],
+        rows,
+    )
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html(report: &Report) -> String {
+    let messages = report.locale.messages();
+    let charts: String = report
+        .charts
+        .iter()
+        .map(|chart| {
+            let rows: String = chart
+                .shares
+                .iter()
+                .map(|s| {
+                    format!(
+                        concat!(
+                            "<tr><td>{}</td>",
+                            "<td class=\"bar\"><span style=\"width: {:.1}%; background: {}\">",
+                            "</span></td><td class=\"detail\">{}</td></tr>\n"
+                        ),
+                        escape_html(&s.name),
+                        s.percentage,
+                        escape_html(&s.color),
+                        escape_html(&s.detail)
+                    )
+                })
+                .collect();
+            format!(
+                "<h2>{}</h2>\n<table>\n{}</table>\n",
+                escape_html(&chart.title),
+                rows
+            )
+        })
+        .collect();
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; max-width: 40em; margin: 2em auto; color: #000000; }}
+h1, h2 {{ color: {title_color}; }}
+table {{ width: 100%; border-collapse: collapse; }}
+td {{ padding: 0.2em 0.5em; }}
+td.bar {{ width: 50%; background: {track_color}; }}
+td.bar span {{ display: block; height: 0.6em; }}
+td.detail {{ text-align: right; white-space: nowrap; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p>{total_stars}</p>
+{charts}</body>
+</html>
+"#,
+        lang = report.locale.code(),
+        title = escape_html(messages.card_title),
+        title_color = renderer::TITLE_COLOR,
+        track_color = renderer::BAR_TRACK_COLOR,
+        total_stars = escape_html(&total_stars(report)),
+        charts = charts
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::RepositoryStat;
+    use crate::test_support::create_stat;
+    use chrono::{DateTime, Duration, Utc};
+
+    fn create_report() -> Report {
+        let share = |name: &str, percentage: f64, detail: &str| Share {
+            name: name.to_string(),
+            color: "#dea584".to_string(),
+            percentage,
+            detail: detail.to_string(),
+        };
+        Report {
+            locale: Locale::En,
+            star_count: 1234,
+            charts: vec![Chart {
+                title: "Top Languages".to_string(),
+                shares: vec![
+                    share("C|C++", 60.0, "60.0%"),
+                    share("<Rust>", 40.0, "40.0%"),
+                ],
+            }],
+            languages: vec![LanguageSummaryValue {
+                name: "Rust".to_string(),
+                color: "#dea584".to_string(),
+                size: 300,
+            }],
+            total_language_size: 1200,
+            repositories: vec![RepositoryRow {
+                stargazer_count: 5,
+                total_commit_count: 10,
+                ..RepositoryRow::new(
+                    "foo, bar".to_string(),
+                    RowKind::Repository(Box::new(RepositoryStat {
+                        topics: vec!["cli".to_string(), "rust".to_string()],
+                        ..create_stat("foo, bar")
+                    })),
+                )
+            }],
+        }
+    }
+
+    #[test]
+    fn test_markdown() {
+        assert_eq!(
+            markdown(&create_report()),
+            concat!(
+                "# GitHub Stats\n\nTotal Stars Earned: 1,234\n\n",
+                "| Top Languages | % |\n| --- | ---: |\n",
+                "| C\\|C++ | 60.0% |\n| &lt;Rust&gt; | 40.0% |\n"
+            )
+        );
+
+        let mut report = create_report();
+        report.charts[0].shares[0].name = "`a` & <b>".to_string();
+        assert!(markdown_tables(&report).contains("| \\`a\\` &amp; &lt;b&gt; | 60.0% |"));
+    }
+
+    #[test]
+    fn test_csv() {
+        let report = create_report();
+        assert_eq!(
+            languages_csv(&report),
+            "name,color,size,percentage\nRust,#dea584,300,25.00\n"
+        );
+        assert_eq!(
+            repositories_csv(&report),
+            concat!(
+                "name,private,fork,archived,stars,total_commits,period_commits,topics\n",
+                "\"foo, bar\",false,false,false,5,10,0,cli rust\n"
+            )
+        );
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_anonymized_rows() {
+        let config = toml::from_str(
+            r#"
+            languages_count = 10
+            ignore_repositories = []
+            ignore_languages = []
+            [language_mapping]
+            [rename_language]
+            [private_repositories]
+            commits = "anonymize"
+            "#,
+        )
+        .unwrap();
+        let secret = RepositoryStat {
+            id: "R_secret".to_string(),
+            is_private: true,
+            topics: vec!["internal".to_string()],
+            total_commit_count: 3,
+            ..create_stat("secret")
+        };
+        let now: DateTime<Utc> = "2023-08-08T00:00:00Z".parse().unwrap();
+        let renderer = Renderer::with_config(
+            vec![secret],
+            Default::default(),
+            Default::default(),
+            config,
+            (now - Duration::days(7), now),
+        );
+        let report = Report::new(&renderer);
+        assert_eq!(report.repositories[0].kind, RowKind::Anonymized);
+        assert_eq!(
+            repositories_csv(&report),
+            concat!(
+                "name,private,fork,archived,stars,total_commits,period_commits,topics\n",
+                "private repo #1,true,,,0,3,0,\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        let html = html(&create_report());
+        assert!(html.starts_with("<!DOCTYPE html>\n<html lang=\"en\">"));
+        assert!(html.contains("<td>&lt;Rust&gt;</td>"));
+        assert!(html.contains("width: 60.0%; background: #dea584"));
+        assert!(html.contains("<p>Total Stars Earned: 1,234</p>"));
+    }
+}
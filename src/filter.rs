@@ -0,0 +1,226 @@
+use crate::graphql::RepositoryStat;
+use chrono::{DateTime, Duration, Utc};
+use regex::Regex;
+use serde::Deserialize;
+
+///
+/// Rules that decide which repositories are counted. A repository must pass every rule.
+///
+#[derive(Debug, Deserialize, Clone)]
+pub struct Filter {
+    /// When not empty, only repositories whose name matches one of these are counted.
+    #[serde(default)]
+    pub include_names: Vec<NamePattern>,
+    #[serde(default)]
+    pub exclude_names: Vec<NamePattern>,
+    /// When not empty, only repositories with one of these topics are counted.
+    #[serde(default)]
+    pub include_topics: Vec<String>,
+    #[serde(default)]
+    pub exclude_topics: Vec<String>,
+    #[serde(default = "FlagFilter::exclude")]
+    pub fork: FlagFilter,
+    #[serde(default)]
+    pub archived: FlagFilter,
+    #[serde(default)]
+    pub template: FlagFilter,
+    #[serde(default)]
+    pub private: FlagFilter,
+    #[serde(default)]
+    pub min_stars: i64,
+    /// Compared with the commits of the default branch over the whole history.
+    #[serde(default)]
+    pub min_commits: i64,
+    /// Repositories that have not been pushed to for longer than this are not counted.
+    #[serde(default)]
+    pub max_push_age_days: Option<i64>,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self {
+            include_names: Vec::new(),
+            exclude_names: Vec::new(),
+            include_topics: Vec::new(),
+            exclude_topics: Vec::new(),
+            fork: FlagFilter::Exclude,
+            archived: FlagFilter::Include,
+            template: FlagFilter::Include,
+            private: FlagFilter::Include,
+            min_stars: 0,
+            min_commits: 0,
+            max_push_age_days: None,
+        }
+    }
+}
+
+///
+/// What to do with the repositories that have a flag such as `is_archived` set
+///
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagFilter {
+    #[default]
+    Include,
+    Exclude,
+    /// Count only the repositories that have the flag.
+    Only,
+}
+
+impl FlagFilter {
+    fn exclude() -> Self {
+        FlagFilter::Exclude
+    }
+
+    ///
+    /// The value of a query argument such as `isFork` that asks GitHub for the accepted
+    /// repositories, `None` when both are accepted
+    ///
+    pub fn flag(&self) -> Option<bool> {
+        match self {
+            FlagFilter::Include => None,
+            FlagFilter::Exclude => Some(false),
+            FlagFilter::Only => Some(true),
+        }
+    }
+
+    fn accepts(&self, flag: bool) -> bool {
+        match self {
+            FlagFilter::Include => true,
+            FlagFilter::Exclude => !flag,
+            FlagFilter::Only => flag,
+        }
+    }
+}
+
+///
+/// A repository name pattern: a regular expression written as `/.../`, or a glob where `*`
+/// matches any characters and `?` one character
+///
+#[derive(Debug, Deserialize, Clone)]
+#[serde(try_from = "String")]
+pub struct NamePattern(Regex);
+
+impl TryFrom<String> for NamePattern {
+    type Error = regex::Error;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        let regex = match pattern.strip_prefix('/').and_then(|p| p.strip_suffix('/')) {
+            Some(regex) => regex.to_string(),
+            None => {
+                let glob = pattern
+                    .split('*')
+                    .map(|part| {
+                        part.split('?')
+                            .map(regex::escape)
+                            .collect::<Vec<_>>()
+                            .join(".")
+                    })
+                    .collect::<Vec<_>>()
+                    .join(".*");
+                format!("^{}$", glob)
+            }
+        };
+        Regex::new(&regex).map(NamePattern)
+    }
+}
+
+impl NamePattern {
+    pub fn is_match(&self, name: &str) -> bool {
+        self.0.is_match(name)
+    }
+}
+
+impl Filter {
+    ///
+    /// Whether the repository passes every rule. `now` is the time the push age is measured at.
+    ///
+    pub fn matches(&self, stat: &RepositoryStat, now: DateTime<Utc>) -> bool {
+        let has_topic = |topics: &[String]| stat.topics.iter().any(|t| topics.contains(t));
+        let has_name = |patterns: &[NamePattern]| patterns.iter().any(|p| p.is_match(&stat.name));
+        (self.include_names.is_empty() || has_name(&self.include_names))
+            && !has_name(&self.exclude_names)
+            && (self.include_topics.is_empty() || has_topic(&self.include_topics))
+            && !has_topic(&self.exclude_topics)
+            && self.fork.accepts(stat.is_fork)
+            && self.archived.accepts(stat.is_archived)
+            && self.template.accepts(stat.is_template)
+            && self.private.accepts(stat.is_private)
+            && stat.stargazer_count >= self.min_stars
+            && stat.total_commit_count >= self.min_commits
+            && self
+                .max_push_age_days
+                .map_or(true, |days| now - stat.pushed_at <= Duration::days(days))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn create_stat(name: &str, topics: &[&str]) -> RepositoryStat {
+        RepositoryStat {
+            disk_usage: 100,
+            stargazer_count: 5,
+            topics: topics.iter().map(|t| t.to_string()).collect(),
+            total_commit_count: 50,
+            ..test_support::create_stat(name)
+        }
+    }
+
+    #[test]
+    fn test_name_pattern() {
+        let pattern = |p: &str| NamePattern::try_from(p.to_string()).unwrap();
+        assert!(pattern("dotfiles*").is_match("dotfiles-mac"));
+        assert!(!pattern("dotfiles*").is_match("my-dotfiles"));
+        assert!(pattern("a?c.rs").is_match("abc.rs"));
+        assert!(!pattern("a?c.rs").is_match("abcxrs"));
+        assert!(pattern("/^tmp-|-sandbox$/").is_match("rust-sandbox"));
+        assert!(NamePattern::try_from("/(/".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_filter() {
+        let now = "2023-08-08T00:00:00Z".parse().unwrap();
+        let stat = create_stat("foo", &["cli"]);
+        assert!(Filter::default().matches(&stat, now));
+
+        let mut fork = create_stat("foo", &[]);
+        fork.is_fork = true;
+        assert!(!Filter::default().matches(&fork, now));
+        assert_eq!(Filter::default().fork.flag(), Some(false));
+        assert_eq!(FlagFilter::Only.flag(), Some(true));
+        assert_eq!(FlagFilter::Include.flag(), None);
+
+        let filter: Filter = toml::from_str(
+            r#"
+            exclude_names = ["tmp-*"]
+            include_topics = ["cli", "web"]
+            exclude_topics = ["wip"]
+            archived = "only"
+            min_stars = 3
+            max_push_age_days = 30
+            "#,
+        )
+        .unwrap();
+        let mut archived = create_stat("foo", &["cli"]);
+        archived.is_archived = true;
+        assert!(filter.matches(&archived, now));
+        assert!(!filter.matches(&stat, now));
+
+        let mut rejected = vec![
+            create_stat("tmp-foo", &["cli"]),
+            create_stat("foo", &["docs"]),
+            create_stat("foo", &["cli", "wip"]),
+            create_stat("foo", &["cli"]),
+            create_stat("foo", &["cli"]),
+        ];
+        rejected[3].stargazer_count = 2;
+        rejected[4].pushed_at = "2023-07-01T00:00:00Z".parse().unwrap();
+        for stat in &mut rejected {
+            stat.is_archived = true;
+            assert!(!filter.matches(stat, now), "{:?}", stat);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+///
+/// Advance widths of the printable ASCII characters (U+0020..U+007E) in 1/1000 em.
+///
+/// The chart CSS asks for 'Segoe UI', Ubuntu, Sans-Serif. Which of them is used depends on
+/// the viewer, so the metrics of Helvetica/Arial are used instead: they are within a few
+/// percent of the configured fonts and slightly wider than Segoe UI.
+///
+#[rustfmt::skip]
+const ASCII_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, // ' '..'/'
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, // '0'..'?'
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, // '@'..'O'
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556, // 'P'..'_'
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556, // '`'..'o'
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584, // 'p'..'~'
+];
+
+/// Width used for characters outside of the table that are not wide (East Asian) characters.
+const DEFAULT_WIDTH: u16 = 556;
+
+/// Width used for full-width characters such as CJK ideographs and kana.
+const WIDE_WIDTH: u16 = 1000;
+
+/// Width of the horizontal ellipsis used when truncating labels.
+const ELLIPSIS_WIDTH: u16 = 1000;
+
+pub const ELLIPSIS: char = '…';
+
+///
+/// Estimate the rendered width in pixels of `text` drawn with the chart font at `font_size` px.
+///
+pub fn measure_text(text: &str, font_size: f64) -> f64 {
+    let units: u32 = text.chars().map(|c| char_width(c) as u32).sum();
+    units as f64 * font_size / 1000.0
+}
+
+fn char_width(c: char) -> u16 {
+    match c {
+        ' '..='~' => ASCII_WIDTHS[c as usize - ' ' as usize],
+        ELLIPSIS => ELLIPSIS_WIDTH,
+        _ if is_wide(c) => WIDE_WIDTH,
+        _ => DEFAULT_WIDTH,
+    }
+}
+
+fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x20000..=0x3FFFD)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_text() {
+        assert_eq!(measure_text("", 9.0), 0.0);
+        // "Rust" = 722 + 556 + 500 + 278 units
+        assert!((measure_text("Rust", 10.0) - 20.56).abs() < 1e-9);
+        assert!(measure_text("WWW", 9.0) > measure_text("iii", 9.0));
+        assert_eq!(measure_text("日本", 10.0), 20.0);
+        assert_eq!(measure_text("…", 9.0), 9.0);
+    }
+}
@@ -1 +1,3 @@
 pub mod github_stats;
+pub mod stargazers;
+pub mod user_stats;
@@ -4,7 +4,7 @@ pub mod git_hub_stats {
     #![allow(dead_code)]
     use std::result::Result;
     pub const OPERATION_NAME: &str = "GitHubStats";
-    pub const QUERY : & str = "query GitHubStats($from: GitTimestamp!, $to: GitTimestamp!) {\n  viewer {\n    repositories(isFork: false, first: 100) {\n      nodes {\n        id\n        name\n        isPrivate\n        isFork\n        isArchived\n        isTemplate\n        diskUsage\n        stargazerCount\n        pushedAt\n        repositoryTopics(first: 100) {\n          edges {\n            node {\n              topic {\n                name\n              }\n            }\n          }\n        }\n        languages(first: 100) {\n          edges {\n            node {\n              name\n              color\n            }\n            size\n          }\n        }\n        defaultBranchRef {\n          target {\n            __typename\n            ... on Commit {\n              commitHistoryPeriod: history(since: $from, until: $to) {\n                totalCount\n              }\n              commitHistoryAll: history {\n                totalCount\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n" ;
+    pub const QUERY : & str = "query GitHubStats($from: GitTimestamp!, $to: GitTimestamp!, $isFork: Boolean, $stargazers: Boolean!) {\n  viewer {\n    login\n    repositories(first: 100, isFork: $isFork) {\n      nodes {\n        ...RepositoryFields\n      }\n    }\n  }\n}\nfragment RepositoryFields on Repository {\n  id\n  name\n  nameWithOwner\n  isPrivate\n  isFork\n  isArchived\n  isTemplate\n  diskUsage\n  stargazerCount\n  stargazers(first: 100, orderBy: { field: STARRED_AT, direction: ASC }) @include(if: $stargazers) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    edges {\n      starredAt\n    }\n  }\n  pushedAt\n  repositoryTopics(first: 100) {\n    edges {\n      node {\n        topic {\n          name\n        }\n      }\n    }\n  }\n  languages(first: 100) {\n    edges {\n      node {\n        name\n        color\n      }\n      size\n    }\n  }\n  defaultBranchRef {\n    target {\n      __typename\n      ... on Commit {\n        commitHistoryPeriod: history(since: $from, until: $to) {\n          totalCount\n        }\n        commitHistoryAll: history {\n          totalCount\n        }\n      }\n    }\n  }\n}\n" ;
     use super::*;
     use serde::{Deserialize, Serialize};
     #[allow(dead_code)]
@@ -17,28 +17,12 @@ pub mod git_hub_stats {
     type ID = String;
     type DateTime = crate::graphql::custom_scalars::DateTime;
     type GitTimestamp = crate::graphql::custom_scalars::GitTimestamp;
-    #[derive(Serialize, Debug)]
-    pub struct Variables {
-        pub from: GitTimestamp,
-        pub to: GitTimestamp,
-    }
-    impl Variables {}
-    #[derive(Deserialize, Serialize, Debug)]
-    pub struct ResponseData {
-        pub viewer: GitHubStatsViewer,
-    }
-    #[derive(Deserialize, Serialize, Debug)]
-    pub struct GitHubStatsViewer {
-        pub repositories: GitHubStatsViewerRepositories,
-    }
     #[derive(Deserialize, Serialize, Debug)]
-    pub struct GitHubStatsViewerRepositories {
-        pub nodes: Option<Vec<Option<GitHubStatsViewerRepositoriesNodes>>>,
-    }
-    #[derive(Deserialize, Serialize, Debug)]
-    pub struct GitHubStatsViewerRepositoriesNodes {
+    pub struct RepositoryFields {
         pub id: ID,
         pub name: String,
+        #[serde(rename = "nameWithOwner")]
+        pub name_with_owner: String,
         #[serde(rename = "isPrivate")]
         pub is_private: Boolean,
         #[serde(rename = "isFork")]
@@ -51,75 +35,116 @@ pub mod git_hub_stats {
         pub disk_usage: Option<Int>,
         #[serde(rename = "stargazerCount")]
         pub stargazer_count: Int,
+        pub stargazers: Option<RepositoryFieldsStargazers>,
         #[serde(rename = "pushedAt")]
         pub pushed_at: Option<DateTime>,
         #[serde(rename = "repositoryTopics")]
-        pub repository_topics: GitHubStatsViewerRepositoriesNodesRepositoryTopics,
-        pub languages: Option<GitHubStatsViewerRepositoriesNodesLanguages>,
+        pub repository_topics: RepositoryFieldsRepositoryTopics,
+        pub languages: Option<RepositoryFieldsLanguages>,
         #[serde(rename = "defaultBranchRef")]
-        pub default_branch_ref: Option<GitHubStatsViewerRepositoriesNodesDefaultBranchRef>,
+        pub default_branch_ref: Option<RepositoryFieldsDefaultBranchRef>,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsStargazers {
+        #[serde(rename = "pageInfo")]
+        pub page_info: RepositoryFieldsStargazersPageInfo,
+        pub edges: Option<Vec<Option<RepositoryFieldsStargazersEdges>>>,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsStargazersPageInfo {
+        #[serde(rename = "hasNextPage")]
+        pub has_next_page: Boolean,
+        #[serde(rename = "endCursor")]
+        pub end_cursor: Option<String>,
     }
     #[derive(Deserialize, Serialize, Debug)]
-    pub struct GitHubStatsViewerRepositoriesNodesRepositoryTopics {
-        pub edges: Option<Vec<Option<GitHubStatsViewerRepositoriesNodesRepositoryTopicsEdges>>>,
+    pub struct RepositoryFieldsStargazersEdges {
+        #[serde(rename = "starredAt")]
+        pub starred_at: DateTime,
     }
     #[derive(Deserialize, Serialize, Debug)]
-    pub struct GitHubStatsViewerRepositoriesNodesRepositoryTopicsEdges {
-        pub node: Option<GitHubStatsViewerRepositoriesNodesRepositoryTopicsEdgesNode>,
+    pub struct RepositoryFieldsRepositoryTopics {
+        pub edges: Option<Vec<Option<RepositoryFieldsRepositoryTopicsEdges>>>,
     }
     #[derive(Deserialize, Serialize, Debug)]
-    pub struct GitHubStatsViewerRepositoriesNodesRepositoryTopicsEdgesNode {
-        pub topic: GitHubStatsViewerRepositoriesNodesRepositoryTopicsEdgesNodeTopic,
+    pub struct RepositoryFieldsRepositoryTopicsEdges {
+        pub node: Option<RepositoryFieldsRepositoryTopicsEdgesNode>,
     }
     #[derive(Deserialize, Serialize, Debug)]
-    pub struct GitHubStatsViewerRepositoriesNodesRepositoryTopicsEdgesNodeTopic {
+    pub struct RepositoryFieldsRepositoryTopicsEdgesNode {
+        pub topic: RepositoryFieldsRepositoryTopicsEdgesNodeTopic,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsRepositoryTopicsEdgesNodeTopic {
         pub name: String,
     }
     #[derive(Deserialize, Serialize, Debug)]
-    pub struct GitHubStatsViewerRepositoriesNodesLanguages {
-        pub edges: Option<Vec<Option<GitHubStatsViewerRepositoriesNodesLanguagesEdges>>>,
+    pub struct RepositoryFieldsLanguages {
+        pub edges: Option<Vec<Option<RepositoryFieldsLanguagesEdges>>>,
     }
     #[derive(Deserialize, Serialize, Debug)]
-    pub struct GitHubStatsViewerRepositoriesNodesLanguagesEdges {
-        pub node: GitHubStatsViewerRepositoriesNodesLanguagesEdgesNode,
+    pub struct RepositoryFieldsLanguagesEdges {
+        pub node: RepositoryFieldsLanguagesEdgesNode,
         pub size: Int,
     }
     #[derive(Deserialize, Serialize, Debug)]
-    pub struct GitHubStatsViewerRepositoriesNodesLanguagesEdgesNode {
+    pub struct RepositoryFieldsLanguagesEdgesNode {
         pub name: String,
         pub color: Option<String>,
     }
     #[derive(Deserialize, Serialize, Debug)]
-    pub struct GitHubStatsViewerRepositoriesNodesDefaultBranchRef {
-        pub target: Option<GitHubStatsViewerRepositoriesNodesDefaultBranchRefTarget>,
+    pub struct RepositoryFieldsDefaultBranchRef {
+        pub target: Option<RepositoryFieldsDefaultBranchRefTarget>,
     }
     #[derive(Deserialize, Serialize, Debug)]
     #[serde(tag = "__typename")]
-    pub enum GitHubStatsViewerRepositoriesNodesDefaultBranchRefTarget {
+    pub enum RepositoryFieldsDefaultBranchRefTarget {
         Blob,
-        Commit(GitHubStatsViewerRepositoriesNodesDefaultBranchRefTargetOnCommit),
+        Commit(RepositoryFieldsDefaultBranchRefTargetOnCommit),
         Tag,
         Tree,
     }
     #[derive(Deserialize, Serialize, Debug)]
-    pub struct GitHubStatsViewerRepositoriesNodesDefaultBranchRefTargetOnCommit {
+    pub struct RepositoryFieldsDefaultBranchRefTargetOnCommit {
         #[serde(rename = "commitHistoryPeriod")]
         pub commit_history_period:
-            GitHubStatsViewerRepositoriesNodesDefaultBranchRefTargetOnCommitCommitHistoryPeriod,
+            RepositoryFieldsDefaultBranchRefTargetOnCommitCommitHistoryPeriod,
         #[serde(rename = "commitHistoryAll")]
-        pub commit_history_all:
-            GitHubStatsViewerRepositoriesNodesDefaultBranchRefTargetOnCommitCommitHistoryAll,
+        pub commit_history_all: RepositoryFieldsDefaultBranchRefTargetOnCommitCommitHistoryAll,
     }
     #[derive(Deserialize, Serialize, Debug)]
-    pub struct GitHubStatsViewerRepositoriesNodesDefaultBranchRefTargetOnCommitCommitHistoryPeriod {
+    pub struct RepositoryFieldsDefaultBranchRefTargetOnCommitCommitHistoryPeriod {
         #[serde(rename = "totalCount")]
         pub total_count: Int,
     }
     #[derive(Deserialize, Serialize, Debug)]
-    pub struct GitHubStatsViewerRepositoriesNodesDefaultBranchRefTargetOnCommitCommitHistoryAll {
+    pub struct RepositoryFieldsDefaultBranchRefTargetOnCommitCommitHistoryAll {
         #[serde(rename = "totalCount")]
         pub total_count: Int,
     }
+    #[derive(Serialize, Debug)]
+    pub struct Variables {
+        pub from: GitTimestamp,
+        pub to: GitTimestamp,
+        #[serde(rename = "isFork")]
+        pub is_fork: Option<Boolean>,
+        pub stargazers: Boolean,
+    }
+    impl Variables {}
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct ResponseData {
+        pub viewer: GitHubStatsViewer,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct GitHubStatsViewer {
+        pub login: String,
+        pub repositories: GitHubStatsViewerRepositories,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct GitHubStatsViewerRepositories {
+        pub nodes: Option<Vec<Option<GitHubStatsViewerRepositoriesNodes>>>,
+    }
+    pub type GitHubStatsViewerRepositoriesNodes = RepositoryFields;
 }
 impl graphql_client::GraphQLQuery for GitHubStats {
     type Variables = git_hub_stats::Variables;
@@ -0,0 +1,63 @@
+#![allow(clippy::all, warnings)]
+pub struct Stargazers;
+pub mod stargazers {
+    #![allow(dead_code)]
+    use std::result::Result;
+    pub const OPERATION_NAME: &str = "Stargazers";
+    pub const QUERY : & str = "query Stargazers($owner: String!, $name: String!, $after: String) {\n  repository(owner: $owner, name: $name) {\n    stargazers(first: 100, after: $after, orderBy: { field: STARRED_AT, direction: ASC }) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      edges {\n        starredAt\n      }\n    }\n  }\n}\n" ;
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    #[allow(dead_code)]
+    type Boolean = bool;
+    #[allow(dead_code)]
+    type Float = f64;
+    #[allow(dead_code)]
+    type Int = i64;
+    #[allow(dead_code)]
+    type ID = String;
+    type DateTime = crate::graphql::custom_scalars::DateTime;
+    #[derive(Serialize, Debug)]
+    pub struct Variables {
+        pub owner: String,
+        pub name: String,
+        pub after: Option<String>,
+    }
+    impl Variables {}
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct ResponseData {
+        pub repository: Option<StargazersRepository>,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct StargazersRepository {
+        pub stargazers: StargazersRepositoryStargazers,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct StargazersRepositoryStargazers {
+        #[serde(rename = "pageInfo")]
+        pub page_info: StargazersRepositoryStargazersPageInfo,
+        pub edges: Option<Vec<Option<StargazersRepositoryStargazersEdges>>>,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct StargazersRepositoryStargazersPageInfo {
+        #[serde(rename = "hasNextPage")]
+        pub has_next_page: Boolean,
+        #[serde(rename = "endCursor")]
+        pub end_cursor: Option<String>,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct StargazersRepositoryStargazersEdges {
+        #[serde(rename = "starredAt")]
+        pub starred_at: DateTime,
+    }
+}
+impl graphql_client::GraphQLQuery for Stargazers {
+    type Variables = stargazers::Variables;
+    type ResponseData = stargazers::ResponseData;
+    fn build_query(variables: Self::Variables) -> ::graphql_client::QueryBody<Self::Variables> {
+        graphql_client::QueryBody {
+            variables,
+            query: stargazers::QUERY,
+            operation_name: stargazers::OPERATION_NAME,
+        }
+    }
+}
@@ -0,0 +1,160 @@
+#![allow(clippy::all, warnings)]
+pub struct UserStats;
+pub mod user_stats {
+    #![allow(dead_code)]
+    use std::result::Result;
+    pub const OPERATION_NAME: &str = "UserStats";
+    pub const QUERY : & str = "query UserStats($login: String!, $from: GitTimestamp!, $to: GitTimestamp!, $isFork: Boolean, $stargazers: Boolean!) {\n  user(login: $login) {\n    login\n    repositories(first: 100, isFork: $isFork) {\n      nodes {\n        ...RepositoryFields\n      }\n    }\n  }\n}\nfragment RepositoryFields on Repository {\n  id\n  name\n  nameWithOwner\n  isPrivate\n  isFork\n  isArchived\n  isTemplate\n  diskUsage\n  stargazerCount\n  stargazers(first: 100, orderBy: { field: STARRED_AT, direction: ASC }) @include(if: $stargazers) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    edges {\n      starredAt\n    }\n  }\n  pushedAt\n  repositoryTopics(first: 100) {\n    edges {\n      node {\n        topic {\n          name\n        }\n      }\n    }\n  }\n  languages(first: 100) {\n    edges {\n      node {\n        name\n        color\n      }\n      size\n    }\n  }\n  defaultBranchRef {\n    target {\n      __typename\n      ... on Commit {\n        commitHistoryPeriod: history(since: $from, until: $to) {\n          totalCount\n        }\n        commitHistoryAll: history {\n          totalCount\n        }\n      }\n    }\n  }\n}\n" ;
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    #[allow(dead_code)]
+    type Boolean = bool;
+    #[allow(dead_code)]
+    type Float = f64;
+    #[allow(dead_code)]
+    type Int = i64;
+    #[allow(dead_code)]
+    type ID = String;
+    type DateTime = crate::graphql::custom_scalars::DateTime;
+    type GitTimestamp = crate::graphql::custom_scalars::GitTimestamp;
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFields {
+        pub id: ID,
+        pub name: String,
+        #[serde(rename = "nameWithOwner")]
+        pub name_with_owner: String,
+        #[serde(rename = "isPrivate")]
+        pub is_private: Boolean,
+        #[serde(rename = "isFork")]
+        pub is_fork: Boolean,
+        #[serde(rename = "isArchived")]
+        pub is_archived: Boolean,
+        #[serde(rename = "isTemplate")]
+        pub is_template: Boolean,
+        #[serde(rename = "diskUsage")]
+        pub disk_usage: Option<Int>,
+        #[serde(rename = "stargazerCount")]
+        pub stargazer_count: Int,
+        pub stargazers: Option<RepositoryFieldsStargazers>,
+        #[serde(rename = "pushedAt")]
+        pub pushed_at: Option<DateTime>,
+        #[serde(rename = "repositoryTopics")]
+        pub repository_topics: RepositoryFieldsRepositoryTopics,
+        pub languages: Option<RepositoryFieldsLanguages>,
+        #[serde(rename = "defaultBranchRef")]
+        pub default_branch_ref: Option<RepositoryFieldsDefaultBranchRef>,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsStargazers {
+        #[serde(rename = "pageInfo")]
+        pub page_info: RepositoryFieldsStargazersPageInfo,
+        pub edges: Option<Vec<Option<RepositoryFieldsStargazersEdges>>>,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsStargazersPageInfo {
+        #[serde(rename = "hasNextPage")]
+        pub has_next_page: Boolean,
+        #[serde(rename = "endCursor")]
+        pub end_cursor: Option<String>,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsStargazersEdges {
+        #[serde(rename = "starredAt")]
+        pub starred_at: DateTime,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsRepositoryTopics {
+        pub edges: Option<Vec<Option<RepositoryFieldsRepositoryTopicsEdges>>>,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsRepositoryTopicsEdges {
+        pub node: Option<RepositoryFieldsRepositoryTopicsEdgesNode>,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsRepositoryTopicsEdgesNode {
+        pub topic: RepositoryFieldsRepositoryTopicsEdgesNodeTopic,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsRepositoryTopicsEdgesNodeTopic {
+        pub name: String,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsLanguages {
+        pub edges: Option<Vec<Option<RepositoryFieldsLanguagesEdges>>>,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsLanguagesEdges {
+        pub node: RepositoryFieldsLanguagesEdgesNode,
+        pub size: Int,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsLanguagesEdgesNode {
+        pub name: String,
+        pub color: Option<String>,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsDefaultBranchRef {
+        pub target: Option<RepositoryFieldsDefaultBranchRefTarget>,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum RepositoryFieldsDefaultBranchRefTarget {
+        Blob,
+        Commit(RepositoryFieldsDefaultBranchRefTargetOnCommit),
+        Tag,
+        Tree,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsDefaultBranchRefTargetOnCommit {
+        #[serde(rename = "commitHistoryPeriod")]
+        pub commit_history_period:
+            RepositoryFieldsDefaultBranchRefTargetOnCommitCommitHistoryPeriod,
+        #[serde(rename = "commitHistoryAll")]
+        pub commit_history_all: RepositoryFieldsDefaultBranchRefTargetOnCommitCommitHistoryAll,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsDefaultBranchRefTargetOnCommitCommitHistoryPeriod {
+        #[serde(rename = "totalCount")]
+        pub total_count: Int,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct RepositoryFieldsDefaultBranchRefTargetOnCommitCommitHistoryAll {
+        #[serde(rename = "totalCount")]
+        pub total_count: Int,
+    }
+    #[derive(Serialize, Debug)]
+    pub struct Variables {
+        pub login: String,
+        pub from: GitTimestamp,
+        pub to: GitTimestamp,
+        #[serde(rename = "isFork")]
+        pub is_fork: Option<Boolean>,
+        pub stargazers: Boolean,
+    }
+    impl Variables {}
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct ResponseData {
+        pub user: Option<UserStatsUser>,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct UserStatsUser {
+        pub login: String,
+        pub repositories: UserStatsUserRepositories,
+    }
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct UserStatsUserRepositories {
+        pub nodes: Option<Vec<Option<UserStatsUserRepositoriesNodes>>>,
+    }
+    pub type UserStatsUserRepositoriesNodes = RepositoryFields;
+}
+impl graphql_client::GraphQLQuery for UserStats {
+    type Variables = user_stats::Variables;
+    type ResponseData = user_stats::ResponseData;
+    fn build_query(variables: Self::Variables) -> ::graphql_client::QueryBody<Self::Variables> {
+        graphql_client::QueryBody {
+            variables,
+            query: user_stats::QUERY,
+            operation_name: user_stats::OPERATION_NAME,
+        }
+    }
+}
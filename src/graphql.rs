@@ -1,13 +1,16 @@
 use crate::generated::github_stats::git_hub_stats;
 use crate::generated::github_stats::git_hub_stats::{
-    GitHubStatsViewerRepositoriesNodesDefaultBranchRef,
-    GitHubStatsViewerRepositoriesNodesDefaultBranchRefTarget,
+    RepositoryFieldsDefaultBranchRef, RepositoryFieldsDefaultBranchRefTarget,
 };
 use crate::generated::github_stats::GitHubStats;
+use crate::generated::stargazers::{stargazers, Stargazers};
+use crate::generated::user_stats::{user_stats, UserStats};
 use crate::graphql::git_hub_stats::ResponseData;
 use crate::AppError;
 use graphql_client::GraphQLQuery;
+use log::debug;
 use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::env;
 
@@ -30,16 +33,23 @@ pub struct GraphQLResponse<T> {
 ///
 /// A struct that normalizes the response from GraphQL for easier handling
 ///
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct RepositoryStat {
     pub id: String,
     pub name: String,
+    /// `owner/name`, used to address the repository in other API calls.
+    #[serde(default)]
+    pub name_with_owner: String,
     pub is_private: bool,
     pub is_fork: bool,
     pub is_archived: bool,
     pub is_template: bool,
     pub disk_usage: i64,
     pub stargazer_count: i64,
+    /// When each star was given, oldest first. Only fetched for the star history, and only
+    /// written out with `starred_at` of `[output]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub starred_at: Vec<chrono::DateTime<chrono::Utc>>,
     pub pushed_at: chrono::DateTime<chrono::Utc>,
     pub topics: Vec<String>,
     pub languages: Vec<RepositoryLanguage>,
@@ -50,34 +60,140 @@ pub struct RepositoryStat {
 ///
 /// Data about the languages used in the repository
 ///
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct RepositoryLanguage {
     pub name: String,
     pub color: String,
     pub size: i64,
 }
 
+/// The GraphQL endpoint used unless `GITHUB_GRAPHQL_URL` is set.
+const DEFAULT_GRAPHQL_URL: &str = "https://api.github.com/graphql";
+/// The base of the REST API used unless `GITHUB_API_URL` is set.
+const DEFAULT_API_URL: &str = "https://api.github.com";
+
 ///
-/// Generate a client to access the GitHub API
+/// Where the GitHub API is and the token to call it with
 ///
-fn get_client() -> Result<RequestBuilder, AppError> {
-    let client = Client::builder()
-        .user_agent("MyApp/0.1")
-        .build()
-        .map_err(|_| AppError::GraphQLClientInitError);
-    let token = env::var("GITHUB_TOKEN").expect("error: GITHUB_TOKEN is not set");
-    client.map(|c| c.post("https://api.github.com/graphql").bearer_auth(token))
+#[derive(Debug, Clone)]
+pub struct GitHub {
+    pub graphql_url: String,
+    /// The base of the REST API, without a trailing slash.
+    pub api_url: String,
+    pub token: String,
+}
+
+impl GitHub {
+    ///
+    /// Read `GITHUB_TOKEN`, and `GITHUB_GRAPHQL_URL` and `GITHUB_API_URL` for another host such
+    /// as GitHub Enterprise Server.
+    ///
+    pub fn from_env() -> Self {
+        Self {
+            graphql_url: env::var("GITHUB_GRAPHQL_URL").unwrap_or(DEFAULT_GRAPHQL_URL.to_string()),
+            api_url: env::var("GITHUB_API_URL")
+                .unwrap_or(DEFAULT_API_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            token: env::var("GITHUB_TOKEN").expect("error: GITHUB_TOKEN is not set"),
+        }
+    }
+
+    ///
+    /// Generate a client to access the GitHub API
+    ///
+    fn client(&self) -> Result<Client, AppError> {
+        Client::builder()
+            .user_agent("MyApp/0.1")
+            .build()
+            .map_err(|_| AppError::GraphQLClientInitError)
+    }
+
+    fn graphql(&self) -> Result<RequestBuilder, AppError> {
+        Ok(self
+            .client()?
+            .post(&self.graphql_url)
+            .bearer_auth(&self.token))
+    }
+
+    ///
+    /// A GET request to the REST API, e.g. for the path `/repos/octocat/hello/commits`
+    ///
+    pub fn rest(&self, path: &str) -> Result<RequestBuilder, AppError> {
+        Ok(self
+            .client()?
+            .get(format!("{}{}", self.api_url, path))
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json"))
+    }
 }
 
 ///
-/// Get repository statistics using the GitHub GraphQL API
+/// Get repository statistics using the GitHub GraphQL API. `is_fork` asks for only the forks
+/// or only the other repositories, and `None` for both. The timestamps of the stars are only
+/// fetched with `stargazers`.
 ///
 pub async fn get_github_stats(
+    github: &GitHub,
     from: String,
     to: String,
+    is_fork: Option<bool>,
+    stargazers: bool,
 ) -> Result<GraphQLResponse<ResponseData>, AppError> {
-    let client = get_client()?;
-    let query = GitHubStats::build_query(git_hub_stats::Variables { from, to });
+    let variables = git_hub_stats::Variables {
+        from,
+        to,
+        is_fork,
+        stargazers,
+    };
+    let mut response = post_query::<GitHubStats>(github, variables).await?;
+    complete_stargazers(github, &mut response.data).await?;
+    Ok(response)
+}
+
+///
+/// Get the repository statistics of any user, or `None` when there is no such user.
+/// The query selects the same fields as `GitHubStats`, so its response is read as the
+/// viewer's and shares `normalize`.
+///
+pub async fn get_user_stats(
+    github: &GitHub,
+    login: String,
+    from: String,
+    to: String,
+    is_fork: Option<bool>,
+    stargazers: bool,
+) -> Result<Option<GraphQLResponse<ResponseData>>, AppError> {
+    let variables = user_stats::Variables {
+        login,
+        from,
+        to,
+        is_fork,
+        stargazers,
+    };
+    let response = post_query::<UserStats>(github, variables).await?;
+    let Some(user) = response.data.user else {
+        return Ok(None);
+    };
+    let viewer = serde_json::to_value(user).map_err(|_| AppError::ConvertError)?;
+    let mut data: ResponseData = serde_json::from_value(serde_json::json!({ "viewer": viewer }))
+        .map_err(|_| AppError::JsonDeserializeError)?;
+    complete_stargazers(github, &mut data).await?;
+    Ok(Some(GraphQLResponse { data }))
+}
+
+///
+/// Send one query and deserialize its response
+///
+async fn post_query<Q: GraphQLQuery>(
+    github: &GitHub,
+    variables: Q::Variables,
+) -> Result<GraphQLResponse<Q::ResponseData>, AppError>
+where
+    Q::ResponseData: DeserializeOwned,
+{
+    let client = github.graphql()?;
+    let query = Q::build_query(variables);
 
     let response = client
         .json(&query)
@@ -90,15 +206,76 @@ pub async fn get_github_stats(
         .await
         .map_err(|_| AppError::GraphQLResponseError)?;
 
-    let response: GraphQLResponse<ResponseData> =
-        serde_json::from_str(&body_text).map_err(|_| AppError::JsonDeserializeError)?;
+    serde_json::from_str(&body_text).map_err(|_| AppError::JsonDeserializeError)
+}
 
-    Ok(response)
+///
+/// The main query only returns the first page of stargazers of each repository.
+/// Fetch the remaining pages so that every star has its timestamp.
+///
+async fn complete_stargazers(github: &GitHub, response: &mut ResponseData) -> Result<(), AppError> {
+    let nodes = response.viewer.repositories.nodes.iter_mut().flatten();
+    for node in nodes.flatten() {
+        let Some((owner, name)) = node.name_with_owner.split_once('/') else {
+            continue;
+        };
+        let Some(stargazers) = node.stargazers.as_mut() else {
+            continue;
+        };
+        while stargazers.page_info.has_next_page {
+            debug!(
+                "fetching stargazers of {} after {:?}",
+                node.name_with_owner, stargazers.page_info.end_cursor
+            );
+            let page = post_query::<Stargazers>(
+                github,
+                stargazers::Variables {
+                    owner: owner.to_string(),
+                    name: name.to_string(),
+                    after: stargazers.page_info.end_cursor.clone(),
+                },
+            )
+            .await?;
+            let Some(repository) = page.data.repository else {
+                break;
+            };
+            let edges = repository.stargazers.edges.unwrap_or_default();
+            stargazers
+                .edges
+                .get_or_insert_with(Vec::new)
+                .extend(edges.into_iter().map(|edge| {
+                    edge.map(|e| git_hub_stats::RepositoryFieldsStargazersEdges {
+                        starred_at: e.starred_at,
+                    })
+                }));
+            stargazers.page_info.has_next_page = repository.stargazers.page_info.has_next_page;
+            stargazers.page_info.end_cursor = repository.stargazers.page_info.end_cursor;
+        }
+    }
+    Ok(())
+}
+
+///
+/// The statistics as they are written out and served: without the timestamps of the stars
+/// unless `starred_at` is set, since they grow with every star
+///
+pub fn published(stats: &[RepositoryStat], starred_at: bool) -> Vec<RepositoryStat> {
+    stats
+        .iter()
+        .map(|s| RepositoryStat {
+            starred_at: if starred_at {
+                s.starred_at.clone()
+            } else {
+                Vec::new()
+            },
+            ..s.clone()
+        })
+        .collect()
 }
 
 pub fn normalize(response: ResponseData) -> Vec<RepositoryStat> {
-    let nodes = response.viewer.repositories.nodes.unwrap_or_else(Vec::new);
-    let nodes: Vec<_> = nodes.into_iter().filter_map(|item| item).collect();
+    let nodes = response.viewer.repositories.nodes.unwrap_or_default();
+    let nodes: Vec<_> = nodes.into_iter().flatten().collect();
     let default_date: chrono::DateTime<chrono::Utc> = "9999-12-31T00:00:00Z".parse().unwrap();
 
     let mut result = Vec::new();
@@ -106,42 +283,49 @@ pub fn normalize(response: ResponseData) -> Vec<RepositoryStat> {
         let topics: Vec<_> = node
             .repository_topics
             .edges
-            .unwrap_or_else(Vec::new)
+            .unwrap_or_default()
             .into_iter()
-            .filter_map(|item| item)
-            .map(|t| t.node)
-            .filter_map(|t| t)
+            .flatten()
+            .filter_map(|t| t.node)
             .map(|t| t.topic.name)
             .collect();
 
         let languages: Vec<_> = node
             .languages
-            .map(|n| n.edges)
-            .unwrap_or_else(|| None)
-            .unwrap_or_else(Vec::new)
+            .and_then(|n| n.edges)
+            .unwrap_or_default()
             .into_iter()
-            .filter_map(|item| item)
-            .map(|item| {
-                let lang = RepositoryLanguage {
-                    name: item.node.name,
-                    color: item.node.color.unwrap_or("red".to_string()),
-                    size: item.size,
-                };
-                lang
+            .flatten()
+            .map(|item| RepositoryLanguage {
+                name: item.node.name,
+                color: item.node.color.unwrap_or("red".to_string()),
+                size: item.size,
             })
             .collect();
 
+        let mut starred_at: Vec<_> = node
+            .stargazers
+            .and_then(|s| s.edges)
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .map(|e| e.starred_at)
+            .collect();
+        starred_at.sort();
+
         let commit_count = get_commit_count(node.default_branch_ref).unwrap_or((0, 0));
 
         let item = RepositoryStat {
             id: node.id,
             name: node.name,
+            name_with_owner: node.name_with_owner,
             is_private: node.is_private,
             is_fork: node.is_fork,
             is_archived: node.is_archived,
             is_template: node.is_template,
             disk_usage: node.disk_usage.unwrap_or(-1),
             stargazer_count: node.stargazer_count,
+            starred_at,
             pushed_at: node.pushed_at.unwrap_or(default_date),
             topics,
             languages,
@@ -153,13 +337,11 @@ pub fn normalize(response: ResponseData) -> Vec<RepositoryStat> {
     result
 }
 
-fn get_commit_count(
-    branch_ref: Option<GitHubStatsViewerRepositoriesNodesDefaultBranchRef>,
-) -> Option<(i64, i64)> {
+fn get_commit_count(branch_ref: Option<RepositoryFieldsDefaultBranchRef>) -> Option<(i64, i64)> {
     let branch_ref = branch_ref?;
     let target = branch_ref.target?;
     let result = match target {
-        GitHubStatsViewerRepositoriesNodesDefaultBranchRefTarget::Commit(commit) => Some(commit),
+        RepositoryFieldsDefaultBranchRefTarget::Commit(commit) => Some(commit),
         _ => None,
     };
     let result = result?;
@@ -168,3 +350,131 @@ fn get_commit_count(
         result.commit_history_period.total_count,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Request, Response, Server};
+    use std::convert::Infallible;
+
+    fn read_fixture(name: &str) -> String {
+        std::fs::read_to_string(format!("testdata/graphql/{}", name)).unwrap()
+    }
+
+    ///
+    /// Answer each query with its recorded response, like the GitHub API would.
+    ///
+    async fn answer(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+        let authorization = request.headers()["authorization"].clone();
+        assert_eq!(authorization, "Bearer test-token");
+        let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+        let query: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let fixture = match query["operationName"].as_str().unwrap() {
+            "GitHubStats" => {
+                assert_eq!(query["variables"]["isFork"], false);
+                assert_eq!(query["variables"]["stargazers"], true);
+                "github_stats.json"
+            }
+            "Stargazers" => {
+                assert_eq!(query["variables"]["owner"], "octocat");
+                assert_eq!(query["variables"]["name"], "hello");
+                assert_eq!(query["variables"]["after"], "Y3Vyc29yOjE=");
+                "stargazers.json"
+            }
+            name => panic!("unexpected query {}", name),
+        };
+        Ok(Response::new(Body::from(read_fixture(fixture))))
+    }
+
+    #[test]
+    fn test_normalize() {
+        let response: GraphQLResponse<ResponseData> =
+            serde_json::from_str(&read_fixture("github_stats.json")).unwrap();
+        let stats = normalize(response.data);
+        let names: Vec<_> = stats.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["hello", "empty", "released", "nulls"]);
+
+        let hello = &stats[0];
+        assert_eq!(hello.name_with_owner, "octocat/hello");
+        assert_eq!(hello.disk_usage, 120);
+        assert_eq!(hello.topics, ["cli"]);
+        assert_eq!(hello.languages.len(), 2);
+        assert_eq!(hello.languages[1].color, "red");
+        assert_eq!(
+            (hello.total_commit_count, hello.period_commit_count),
+            (57, 4)
+        );
+        assert_eq!(hello.starred_at.len(), 1);
+
+        // an empty repository has no default branch
+        let empty = &stats[1];
+        assert!(empty.is_private);
+        assert!(empty.languages.is_empty());
+        assert_eq!(
+            (empty.total_commit_count, empty.period_commit_count),
+            (0, 0)
+        );
+
+        // the default branch points to a tag
+        let released = &stats[2];
+        assert!(released.is_fork && released.is_archived);
+        assert_eq!(released.languages[0].size, 1500);
+        assert_eq!(released.total_commit_count, 0);
+
+        let nulls = &stats[3];
+        assert_eq!(nulls.disk_usage, -1);
+        assert_eq!(
+            nulls.pushed_at,
+            "9999-12-31T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert!(nulls.topics.is_empty() && nulls.languages.is_empty());
+        assert!(nulls.starred_at.is_empty());
+        assert_eq!(nulls.total_commit_count, 0);
+    }
+
+    #[test]
+    fn test_published() {
+        let response: GraphQLResponse<ResponseData> =
+            serde_json::from_str(&read_fixture("github_stats.json")).unwrap();
+        let stats = normalize(response.data);
+        let json = serde_json::to_value(published(&stats, false)).unwrap();
+        assert!(json[0].get("starred_at").is_none());
+        let json = serde_json::to_value(published(&stats, true)).unwrap();
+        assert_eq!(json[0]["starred_at"][0], "2023-08-02T10:00:00Z");
+    }
+
+    #[tokio::test]
+    async fn test_get_github_stats() {
+        let make_service = make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(answer)) });
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let github = GitHub {
+            graphql_url: format!("http://{}/graphql", server.local_addr()),
+            api_url: format!("http://{}", server.local_addr()),
+            token: "test-token".to_string(),
+        };
+        tokio::spawn(server);
+
+        let response = get_github_stats(
+            &github,
+            "2023-08-01T00:00:00Z".to_string(),
+            "2023-08-08T00:00:00Z".to_string(),
+            Some(false),
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.data.viewer.login, "octocat");
+        let stats = normalize(response.data);
+        assert_eq!(stats.len(), 4);
+        // the second page of stargazers is fetched and merged, oldest first
+        assert_eq!(
+            stats[0].starred_at,
+            [
+                "2023-07-15T08:30:00Z".parse().unwrap(),
+                "2023-08-02T10:00:00Z".parse::<DateTime<Utc>>().unwrap()
+            ]
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use crate::graphql::RepositoryStat;
+use crate::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+///
+/// The normalized statistics of one run, stored as one line of the history file
+///
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub timestamp: DateTime<Utc>,
+    pub stats: Vec<RepositoryStat>,
+}
+
+///
+/// Append a snapshot to the JSON Lines history file, creating it if necessary.
+///
+pub fn append(path: &Path, snapshot: &Snapshot) -> Result<(), AppError> {
+    let line = serde_json::to_string(snapshot).map_err(|_| AppError::ConvertError)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|_| AppError::HistoryError)?;
+    writeln!(file, "{}", line).map_err(|_| AppError::HistoryError)
+}
+
+///
+/// Load every snapshot of the history file, oldest first. A missing file is an empty history.
+///
+pub fn load(path: &Path) -> Result<Vec<Snapshot>, AppError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(_) => return Err(AppError::HistoryError),
+    };
+    let mut snapshots = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(|_| AppError::JsonDeserializeError))
+        .collect::<Result<Vec<Snapshot>, _>>()?;
+    snapshots.sort_by_key(|s| s.timestamp);
+    Ok(snapshots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_and_load() {
+        let path = std::env::temp_dir().join(format!("history-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert_eq!(load(&path).unwrap(), vec![]);
+
+        let newer = Snapshot {
+            timestamp: "2023-08-02T00:00:00Z".parse().unwrap(),
+            stats: vec![],
+        };
+        let older = Snapshot {
+            timestamp: "2023-08-01T00:00:00Z".parse().unwrap(),
+            stats: vec![],
+        };
+        append(&path, &newer).unwrap();
+        append(&path, &older).unwrap();
+        assert_eq!(load(&path).unwrap(), vec![older, newer]);
+
+        fs::write(&path, "not json\n").unwrap();
+        assert!(load(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
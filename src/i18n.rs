@@ -0,0 +1,330 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+///
+/// The language used for the texts drawn on the card
+///
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Ja,
+}
+
+///
+/// A message catalog. Placeholders in braces are replaced by `fill`.
+///
+#[derive(Debug)]
+pub struct Messages {
+    pub card_title: &'static str,
+    pub header_description: &'static str,
+    pub languages_description: &'static str,
+    pub recent_languages_description: &'static str,
+    pub commits_description: &'static str,
+    pub active_commits_description: &'static str,
+    pub topics_description: &'static str,
+    pub sentence_separator: &'static str,
+    pub list_separator: &'static str,
+    pub total_stars: &'static str,
+    pub top_languages: &'static str,
+    pub top_recent_languages: &'static str,
+    pub top_commits: &'static str,
+    pub top_active_commits: &'static str,
+    pub top_topics: &'static str,
+    pub topic_detail: &'static str,
+    /// The tooltips of the bars of the language and commit charts.
+    pub bytes_tooltip: &'static str,
+    pub commits_tooltip: &'static str,
+    pub stars_trend: &'static str,
+    pub commits_trend: &'static str,
+    pub languages_trend: &'static str,
+    pub trend_description: &'static str,
+    pub star_history: &'static str,
+    pub star_history_summary: &'static str,
+    pub star_history_description: &'static str,
+    pub stars_gained: &'static str,
+    pub private_repository: &'static str,
+    pub private_repositories: &'static str,
+    pub other: &'static str,
+    pub lines_changed: &'static str,
+    pub lines_changed_description: &'static str,
+    pub lines: &'static str,
+    pub diff_title: &'static str,
+    pub diff_added: &'static str,
+    pub diff_removed: &'static str,
+    pub diff_archived: &'static str,
+    pub diff_stars: &'static str,
+    pub diff_commits: &'static str,
+    pub diff_language_gained: &'static str,
+    pub diff_language_lost: &'static str,
+    pub diff_rank: &'static str,
+    pub diff_no_changes: &'static str,
+    pub period_week: &'static str,
+    pub period_days: &'static str,
+    pub recent_week: &'static str,
+    pub recent_days: &'static str,
+    pub generated_before: &'static str,
+    pub generated_after: &'static str,
+    pub date_format: &'static str,
+    pub group_separator: &'static str,
+    pub decimal_separator: &'static str,
+}
+
+const EN: Messages = Messages {
+    card_title: "GitHub Stats",
+    header_description: "Total stars earned: {stars}.",
+    languages_description: "Top languages: {languages}.",
+    recent_languages_description: "Languages used lately: {languages}.",
+    commits_description: "Top commits: {commits}.",
+    active_commits_description: "Top active commits {recent}: {commits}.",
+    topics_description: "Top topics: {topics}.",
+    sentence_separator: " ",
+    list_separator: ", ",
+    total_stars: "Total Stars Earned: {count}",
+    top_languages: "Top Languages",
+    top_recent_languages: "Recent Languages",
+    top_commits: "Top Commits",
+    top_active_commits: "Top Active Commits({period})",
+    top_topics: "Top Topics",
+    topic_detail: "{repositories} repos, {commits} commits",
+    bytes_tooltip: "{name}: {share}% ({bytes} bytes)",
+    commits_tooltip: "{name}: {share}% ({commits} commits)",
+    stars_trend: "Stars Earned",
+    commits_trend: "Total Commits",
+    languages_trend: "Language Share",
+    trend_description: "{title} from {from} to {to}: {values}.",
+    star_history: "Star History",
+    star_history_summary: "{count} stars ({delta} {recent})",
+    star_history_description:
+        "{count} stars as of {to}, {gained} gained since {from}: {repositories}.",
+    stars_gained: "Stars Gained({period})",
+    private_repository: "private repo #{n}",
+    private_repositories: "Private",
+    other: "Other",
+    lines_changed: "Lines Changed This Week",
+    lines_changed_description: "Lines changed in the last week by language: {languages}.",
+    lines: "{count} lines",
+    diff_title: "What's New This Week",
+    diff_added: "New repository: {name}",
+    diff_removed: "Removed repository: {name}",
+    diff_archived: "Archived: {name}",
+    diff_stars: "{name}: {delta} stars ({after})",
+    diff_commits: "{name}: {delta} commits ({after})",
+    diff_language_gained: "New language: {name}",
+    diff_language_lost: "Dropped language: {name}",
+    diff_rank: "{chart}: {name} {before} → {after}",
+    diff_no_changes: "No changes",
+    period_week: "1week",
+    period_days: "{days}days",
+    recent_week: "in the last week",
+    recent_days: "in the last {days} days",
+    generated_before: "Generated by",
+    generated_after: "at {date}",
+    date_format: "%Y-%m-%d",
+    group_separator: ",",
+    decimal_separator: ".",
+};
+
+const JA: Messages = Messages {
+    card_title: "GitHub 統計",
+    header_description: "獲得スター数: {stars}。",
+    languages_description: "使用言語: {languages}。",
+    recent_languages_description: "最近の使用言語: {languages}。",
+    commits_description: "コミット数: {commits}。",
+    active_commits_description: "{recent}のコミット数: {commits}。",
+    topics_description: "トピック: {topics}。",
+    sentence_separator: "",
+    list_separator: "、",
+    total_stars: "獲得スター数: {count}",
+    top_languages: "使用言語",
+    top_recent_languages: "最近の使用言語",
+    top_commits: "コミット数",
+    top_active_commits: "{recent}のコミット数",
+    top_topics: "トピック",
+    topic_detail: "{repositories} リポジトリ, {commits} コミット",
+    bytes_tooltip: "{name}: {share}% ({bytes} バイト)",
+    commits_tooltip: "{name}: {share}% ({commits} コミット)",
+    stars_trend: "獲得スター数の推移",
+    commits_trend: "総コミット数の推移",
+    languages_trend: "使用言語の割合の推移",
+    trend_description: "{from}から{to}までの{title}: {values}。",
+    star_history: "スター獲得の推移",
+    star_history_summary: "スター {count} ({recent} {delta})",
+    star_history_description:
+        "{to}時点のスター数: {count}、{from}以降の獲得数: {gained} ({repositories})。",
+    stars_gained: "{recent}に獲得したスター",
+    private_repository: "プライベートリポジトリ #{n}",
+    private_repositories: "プライベート",
+    other: "その他",
+    lines_changed: "今週変更した行数",
+    lines_changed_description: "直近1週間に変更した行数: {languages}。",
+    lines: "{count} 行",
+    diff_title: "今週の変化",
+    diff_added: "新しいリポジトリ: {name}",
+    diff_removed: "削除されたリポジトリ: {name}",
+    diff_archived: "アーカイブ: {name}",
+    diff_stars: "{name}: スター {delta} ({after})",
+    diff_commits: "{name}: コミット {delta} ({after})",
+    diff_language_gained: "新しい言語: {name}",
+    diff_language_lost: "使われなくなった言語: {name}",
+    diff_rank: "{chart}: {name} {before} → {after}",
+    diff_no_changes: "変化なし",
+    period_week: "1週間",
+    period_days: "{days}日間",
+    recent_week: "直近1週間",
+    recent_days: "直近{days}日間",
+    generated_before: "",
+    generated_after: "により {date} に生成",
+    date_format: "%Y年%m月%d日",
+    group_separator: ",",
+    decimal_separator: ".",
+};
+
+impl Locale {
+    pub fn messages(&self) -> &'static Messages {
+        match self {
+            Locale::En => &EN,
+            Locale::Ja => &JA,
+        }
+    }
+
+    ///
+    /// The language tag, e.g. for the `lang` attribute of HTML.
+    ///
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Ja => "ja",
+        }
+    }
+
+    ///
+    /// Format an integer with the digit grouping of the locale, e.g. `1,234,567`.
+    ///
+    pub fn format_number(&self, n: i64) -> String {
+        let digits = n.unsigned_abs().to_string();
+        let mut result = String::new();
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                result.push_str(self.messages().group_separator);
+            }
+            result.push(c);
+        }
+        if n < 0 {
+            result.insert(0, '-');
+        }
+        result
+    }
+
+    ///
+    /// Format a number with a fixed count of fraction digits, e.g. `12.3`.
+    ///
+    pub fn format_decimal(&self, n: f64, precision: usize) -> String {
+        let formatted = format!("{:.*}", precision, n);
+        let (int_part, frac_part) = match formatted.split_once('.') {
+            Some((i, f)) => (i, Some(f)),
+            None => (formatted.as_str(), None),
+        };
+        let int_part = match int_part.parse::<i64>() {
+            Ok(0) if int_part.starts_with('-') => "-0".to_string(),
+            Ok(i) => self.format_number(i),
+            Err(_) => int_part.to_string(),
+        };
+        match frac_part {
+            Some(f) => format!("{}{}{}", int_part, self.messages().decimal_separator, f),
+            None => int_part,
+        }
+    }
+
+    pub fn format_date(&self, date: &DateTime<Utc>) -> String {
+        date.format(self.messages().date_format).to_string()
+    }
+
+    ///
+    /// Fill the placeholders of a message for a period of `days` ending now: `{period}` as in
+    /// a title, e.g. `30days`, and `{recent}` as in a sentence, e.g. `in the last 30 days`.
+    ///
+    pub fn fill_period(&self, message: &str, days: i64) -> String {
+        let messages = self.messages();
+        let days_text = days.to_string();
+        let (period, recent) = match days {
+            7 => (
+                messages.period_week.to_string(),
+                messages.recent_week.to_string(),
+            ),
+            _ => (
+                fill(messages.period_days, &[("days", &days_text)]),
+                fill(messages.recent_days, &[("days", &days_text)]),
+            ),
+        };
+        fill(message, &[("period", &period), ("recent", &recent)])
+    }
+}
+
+///
+/// Replace the `{name}` placeholders of a message with the given values.
+///
+pub fn fill(message: &str, values: &[(&str, &str)]) -> String {
+    let mut result = message.to_string();
+    for (name, value) in values {
+        result = result.replace(&format!("{{{}}}", name), value);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(Locale::En.format_number(0), "0");
+        assert_eq!(Locale::En.format_number(999), "999");
+        assert_eq!(Locale::En.format_number(1000), "1,000");
+        assert_eq!(Locale::Ja.format_number(1234567), "1,234,567");
+        assert_eq!(Locale::En.format_number(-12345), "-12,345");
+        assert_eq!(Locale::En.format_decimal(12345.678, 1), "12,345.7");
+        assert_eq!(Locale::En.format_decimal(-0.04, 1), "-0.0");
+        assert_eq!(Locale::En.format_decimal(3.0, 0), "3");
+    }
+
+    #[test]
+    fn test_format_date() {
+        let date: DateTime<Utc> = "2023-08-06T12:00:00Z".parse().unwrap();
+        assert_eq!(Locale::En.format_date(&date), "2023-08-06");
+        assert_eq!(Locale::Ja.format_date(&date), "2023年08月06日");
+    }
+
+    #[test]
+    fn test_fill_period() {
+        let en = Locale::En.messages();
+        assert_eq!(
+            Locale::En.fill_period(en.top_active_commits, 7),
+            "Top Active Commits(1week)"
+        );
+        assert_eq!(
+            Locale::En.fill_period(en.active_commits_description, 30),
+            "Top active commits in the last 30 days: {commits}."
+        );
+        let ja = Locale::Ja.messages();
+        assert_eq!(
+            Locale::Ja.fill_period(ja.top_active_commits, 7),
+            "直近1週間のコミット数"
+        );
+        assert_eq!(
+            Locale::Ja.fill_period(ja.stars_gained, 90),
+            "直近90日間に獲得したスター"
+        );
+    }
+
+    #[test]
+    fn test_fill() {
+        let message = Locale::Ja.messages().total_stars;
+        assert_eq!(fill(message, &[("count", "1,024")]), "獲得スター数: 1,024");
+        assert_eq!(fill("{a} and {a}", &[("a", "x")]), "x and x");
+        let message = Locale::Ja.messages().commits_tooltip;
+        let values = [("name", "foo"), ("share", "12.50"), ("commits", "1,024")];
+        assert_eq!(fill(message, &values), "foo: 12.50% (1,024 コミット)");
+    }
+}
@@ -1,12 +1,30 @@
+mod changes;
 mod color;
 mod config;
+mod diff;
+mod envelope;
+mod export;
+mod filter;
+mod font;
 mod generated;
 mod graphql;
+mod history;
+mod i18n;
+mod metrics;
+mod publish;
+mod raster;
+mod readme;
 mod renderer;
+mod schedule;
+mod server;
+#[cfg(test)]
+mod test_support;
 
-use chrono::{Duration, Utc};
-use log::{debug, info};
-use renderer::Renderer;
+use chrono::{DateTime, Duration, Utc};
+use log::{debug, info, warn};
+use renderer::{Panel, Renderer, Trend};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 
 #[derive(Debug)]
 pub enum AppError {
@@ -18,45 +36,297 @@ pub enum AppError {
     JsonDeserializeError,
     ConvertError,
     SvgOutputError,
+    JsonOutputError,
+    PngOutputError,
+    HistoryError,
+    DiffError,
+    ServerError,
+    UserNotFoundError,
+    TooManyRequestsError,
+    RepositoryUnavailableError,
+    ScheduleError,
+    PublishError,
+    ReadmeError,
+    ExportError,
+    ConfigError,
+    UsageError,
 }
 
+const USAGE: &str = "usage:
+    github-summary                   generate the cards from the GitHub API
+    github-summary diff <before.json> <after.json> [--format text|json|svg]
+                                     report what changed between two github_stats.json files
+    github-summary serve             serve the cards over HTTP, see [server] of config.toml
+                                     /card.svg for the token owner, /{login}/card.svg for others
+    github-summary watch             generate the cards now and at every time of [schedule]
+    github-summary schema            print the JSON Schema of github_stats.json";
+
+/// The days of the period of the generated cards.
+pub const PERIOD_DAYS: i64 = 7;
+
 ///
 /// Calculate the duration of the most recent week.
 ///
-fn get_date_range() -> (String, String) {
+fn get_date_range() -> (DateTime<Utc>, DateTime<Utc>) {
     let to = Utc::now();
-    let from = to - Duration::days(7);
-    (from.to_rfc3339(), to.to_rfc3339())
+    let from = to - Duration::days(PERIOD_DAYS);
+    (from, to)
+}
+
+///
+/// Write a file through a temporary file next to it, so that a reader such as a web server
+/// never sees it half written.
+///
+fn write_atomic(path: &str, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let temporary = format!("{}.tmp", path);
+    std::fs::write(&temporary, contents)?;
+    std::fs::rename(&temporary, path)
+}
+
+///
+/// Write a card as `{name}.svg`, and as `{name}.png` when PNG output is enabled.
+///
+fn write_card(
+    name: &str,
+    document: &svg::Document,
+    output: &config::Output,
+) -> Result<(), AppError> {
+    write_atomic(&format!("{}.svg", name), document.to_string())
+        .map_err(|_| AppError::SvgOutputError)?;
+    if output.png {
+        let png = raster::render_png(document, output.png_scale, &output.png_fonts)?;
+        write_atomic(&format!("{}.png", name), png).map_err(|_| AppError::PngOutputError)?;
+    }
+    Ok(())
+}
+
+///
+/// Print the changes between two saved snapshots to stdout.
+///
+fn run_diff(args: &[String]) -> Result<(), AppError> {
+    let mut paths = Vec::new();
+    let mut format = "text";
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => format = args.next().ok_or(AppError::UsageError)?,
+            _ => paths.push(arg),
+        }
+    }
+    let [before, after] = paths.as_slice() else {
+        return Err(AppError::UsageError);
+    };
+    let before = diff::load_stats(std::path::Path::new(before))?;
+    let after = diff::load_stats(std::path::Path::new(after))?;
+    let config = config::try_load()?;
+    let report = diff::diff(before, after, &config, Utc::now());
+
+    match format {
+        "text" => println!("{}", report.lines(config.locale).join("\n")),
+        "json" => println!(
+            "{}",
+            serde_json::to_string_pretty(&report).map_err(|_| AppError::ConvertError)?
+        ),
+        "svg" => println!("{}", renderer::render_diff(&report, &config)),
+        _ => return Err(AppError::UsageError),
+    }
+    Ok(())
 }
 
 #[tokio::main]
 async fn main() -> Result<(), AppError> {
     env_logger::init();
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        None => generate(config::try_load()?, None).await.map(|_| ()),
+        Some("diff") => run_diff(&args[1..]),
+        Some("serve") => server::serve(config::try_load()?).await,
+        Some("watch") => watch().await,
+        Some("schema") => {
+            print!("{}", envelope::JSON_SCHEMA);
+            Ok(())
+        }
+        Some(_) => Err(AppError::UsageError),
+    };
+    if let Err(AppError::UsageError) = result {
+        eprintln!("{}", USAGE);
+    }
+    result
+}
+
+///
+/// Generate the cards now and then at every time of `[schedule]` until the process is stopped.
+/// A failed run is logged and tried again at the next time.
+///
+async fn watch() -> Result<(), AppError> {
+    let mut previous = None;
+    // kept from the last config that could be read, so that a broken edit does not stop it
+    let mut cron = config::Schedule::default().cron;
+    loop {
+        let result = match config::try_load() {
+            Ok(config) => {
+                cron = config.schedule.cron.clone();
+                generate(config, previous).await
+            }
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(hash) => previous = Some(hash),
+            Err(e) => warn!("failed to generate the cards: {:?}", e),
+        }
+        let now = Utc::now();
+        let next = cron.next_after(now).ok_or(AppError::ScheduleError)?;
+        info!("next run at {}", next);
+        tokio::time::sleep((next - now).to_std().unwrap_or_default()).await;
+    }
+}
+
+///
+/// Fetch the statistics and write every output of `config`. Returns the hash of the statistics
+/// and the config, and writes nothing when it equals `previous`.
+///
+async fn generate(config: config::Config, previous: Option<u64>) -> Result<u64, AppError> {
     let date_range = get_date_range();
     info!("date range: from={}, to={}", date_range.0, date_range.1);
 
-    let github_stats = graphql::get_github_stats(date_range.0, date_range.1).await?;
+    let github = graphql::GitHub::from_env();
+    let github_stats = graphql::get_github_stats(
+        &github,
+        date_range.0.to_rfc3339(),
+        date_range.1.to_rfc3339(),
+        config.filter.fork.flag(),
+        config.output.star_history,
+    )
+    .await?;
 
     let github_stats_string =
         serde_json::to_string_pretty(&github_stats).map_err(|_| AppError::ConvertError)?;
     debug!("graphql response: {}", github_stats_string);
 
+    let login = github_stats.data.viewer.login.clone();
     let github_stats = graphql::normalize(github_stats.data);
 
-    let github_stats_json =
-        serde_json::to_string(&github_stats).map_err(|_| AppError::ConvertError)?;
-    std::fs::write("github_stats.json", github_stats_json).map_err(|_| AppError::ConvertError)?;
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(&github_stats)
+        .map_err(|_| AppError::ConvertError)?
+        .hash(&mut hasher);
+    config.hash.hash(&mut hasher);
+    let hash = hasher.finish();
+    let published = graphql::published(&github_stats, config.output.starred_at);
+    // every run is recorded, also one that changed nothing, so that the trend has no gaps;
+    // the trend cards show it with the next run that writes the files
+    let path = std::path::Path::new(&config.history.path);
+    if config.history.enabled {
+        let snapshot = history::Snapshot {
+            timestamp: Utc::now(),
+            stats: published.clone(),
+        };
+        history::append(path, &snapshot)?;
+    }
+    if previous == Some(hash) {
+        info!("the statistics and the config are unchanged, nothing to write");
+        return Ok(hash);
+    }
+    let envelope = envelope::Envelope::new(
+        login,
+        date_range.0,
+        date_range.1,
+        published,
+        config.hash.clone(),
+    );
+    let github_stats_json = serde_json::to_string(&envelope).map_err(|_| AppError::ConvertError)?;
+    write_atomic("github_stats.json", github_stats_json).map_err(|_| AppError::JsonOutputError)?;
 
-    let language_colors = color::get_language_color_settings()
+    let history = if config.history.enabled {
+        history::load(path)?
+    } else {
+        Vec::new()
+    };
+
+    let linguist_languages = color::get_linguist_languages()
         .await
         .map_err(|_| AppError::ConvertError)?;
+    let language_colors = color::get_language_color_settings(&linguist_languages);
+    let language_groups = color::get_language_group_settings(&linguist_languages);
 
-    let mut renderer = Renderer::new(github_stats, language_colors);
+    let mut renderer = Renderer::with_config(
+        github_stats,
+        language_colors.clone(),
+        language_groups.clone(),
+        config.clone(),
+        date_range,
+    );
 
-    let github_stats_svg = renderer.render();
-    std::fs::write("github_stats.svg", github_stats_svg.to_string())
-        .map_err(|_| AppError::ConvertError)?;
+    let output = &config.output;
+    if output.png && output.png_fonts.is_empty() && config.locale == i18n::Locale::Ja {
+        warn!("png_fonts is empty, the Japanese labels of the PNG files are drawn as boxes");
+    }
+    write_card("github_stats", &renderer.render(), output)?;
+    if output.separate_cards {
+        for panel in Panel::ALL {
+            let name = format!("github_stats_{}", panel.name());
+            write_card(&name, &renderer.render_panel(panel), output)?;
+        }
+    }
+    if !output.exports.is_empty() {
+        let report = export::Report::new(&renderer);
+        for format in &output.exports {
+            for (name, contents) in format.export(&report) {
+                write_atomic(name, contents).map_err(|_| AppError::ExportError)?;
+            }
+        }
+    }
+    if output.metrics {
+        write_atomic("github_stats.prom", metrics::render_metrics(&renderer))
+            .map_err(|_| AppError::ExportError)?;
+    }
+    if output.star_history {
+        let document = renderer.render_star_history(date_range.0, date_range.1);
+        write_card("github_stats_stars", &document, output)?;
+    }
+    if config.changes.enabled {
+        let summary = changes::get_language_changes(
+            &github,
+            &renderer.stats,
+            date_range,
+            &linguist_languages,
+            &language_colors,
+            &language_groups,
+            &config,
+        )
+        .await?;
+        write_card(
+            "github_stats_changes",
+            &renderer::render_changes(&summary, &config),
+            output,
+        )?;
+    }
+    if config.history.enabled {
+        for trend in Trend::ALL {
+            let name = format!("github_stats_trend_{}", trend.name());
+            write_card(&name, &renderer.render_trend(&history, trend), output)?;
+        }
+    }
+    if config.readme.enabled {
+        let updated = readme::update(&config.readme, &renderer, hash)?;
+        info!("{} updated: {}", config.readme.path, updated);
+    }
+    if config.publish.enabled {
+        let readme = std::path::Path::new(&config.readme.path);
+        let written = if config.readme.enabled {
+            vec![readme]
+        } else {
+            vec![]
+        };
+        let published = publish::publish(
+            std::path::Path::new("."),
+            &config.publish,
+            &written,
+            Utc::now(),
+        )?;
+        info!("published: {}", published);
+    }
 
-    Ok(())
+    Ok(hash)
 }
@@ -0,0 +1,160 @@
+use crate::config::PrivatePolicy;
+use crate::graphql::RepositoryStat;
+use crate::renderer::Renderer;
+
+/// The media type of the OpenMetrics text format.
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+///
+/// Escape a label value: backslashes, double quotes and line feeds.
+///
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+///
+/// One gauge family: its metadata and one sample for each set of label values.
+///
+fn family(name: &str, help: &str, labels: &[&str], samples: &[(Vec<String>, i64)]) -> String {
+    let mut lines = vec![
+        format!("# TYPE {} gauge", name),
+        format!("# HELP {} {}", name, help),
+    ];
+    lines.extend(samples.iter().map(|(values, sample)| {
+        let labels = labels
+            .iter()
+            .zip(values)
+            .map(|(label, value)| format!("{}=\"{}\"", label, escape_label(value)))
+            .collect::<Vec<_>>();
+        format!("{}{{{}}} {}", name, labels.join(","), sample)
+    }));
+    lines.join("\n") + "\n"
+}
+
+///
+/// The statistics in the OpenMetrics text format. Each repository is labeled with its
+/// `owner/name`, which does not change between runs, so private repositories are only there
+/// when `[private_repositories] commits` includes them under their names.
+///
+pub fn render_metrics(renderer: &Renderer) -> String {
+    let policy = renderer.config.private_repositories.commits;
+    let mut repositories: Vec<_> = renderer
+        .stats
+        .iter()
+        .filter(|r| !r.is_private || policy == PrivatePolicy::Include)
+        .collect();
+    repositories.sort_by(|a, b| a.name_with_owner.cmp(&b.name_with_owner));
+    let samples = |value: fn(&RepositoryStat) -> i64| -> Vec<_> {
+        repositories
+            .iter()
+            .map(|r| (vec![r.name_with_owner.clone()], value(r)))
+            .collect()
+    };
+    let weight = renderer.config.language_weight.name();
+    let mut languages: Vec<_> = renderer
+        .language_summary
+        .data
+        .values()
+        .map(|l| (vec![l.name.clone(), weight.to_string()], l.size))
+        .collect();
+    languages.sort();
+
+    [
+        family(
+            "github_repo_stars",
+            "Stars of the repository.",
+            &["repo"],
+            &samples(|r| r.stargazer_count),
+        ),
+        // not a counter, the commits of a branch go down when its history is rewritten
+        family(
+            "github_repo_commits",
+            "Commits on the default branch of the repository.",
+            &["repo"],
+            &samples(|r| r.total_commit_count),
+        ),
+        family(
+            "github_repo_commits_period",
+            "Commits on the default branch of the repository in the period of the statistics.",
+            &["repo"],
+            &samples(|r| r.period_commit_count),
+        ),
+        family(
+            "github_language_size",
+            "Size of the language over the counted repositories, in bytes weighted by weight.",
+            &["language", "weight"],
+            &languages,
+        ),
+    ]
+    .concat()
+        + "# EOF\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_language, create_stat};
+    use chrono::{DateTime, Duration, Utc};
+
+    #[test]
+    fn test_render_metrics() {
+        let config = toml::from_str(
+            r#"
+            languages_count = 10
+            ignore_repositories = []
+            ignore_languages = []
+            [language_mapping]
+            [rename_language]
+            [private_repositories]
+            commits = "anonymize"
+            "#,
+        )
+        .unwrap();
+        let stat = |name: &str, stars, commits| RepositoryStat {
+            stargazer_count: stars,
+            total_commit_count: commits,
+            period_commit_count: 1,
+            languages: vec![create_language("Rust", "#dea584", 100)],
+            ..create_stat(name)
+        };
+        let secret = RepositoryStat {
+            is_private: true,
+            ..stat("secret", 1, 1)
+        };
+        let now: DateTime<Utc> = "2023-08-08T00:00:00Z".parse().unwrap();
+        let renderer = Renderer::with_config(
+            vec![stat("say-\"hi\"", 3, 10), secret, stat("foo", 0, 5)],
+            Default::default(),
+            Default::default(),
+            config,
+            (now - Duration::days(7), now),
+        );
+        let metrics = render_metrics(&renderer);
+        assert_eq!(
+            metrics,
+            concat!(
+                "# TYPE github_repo_stars gauge\n",
+                "# HELP github_repo_stars Stars of the repository.\n",
+                "github_repo_stars{repo=\"octocat/foo\"} 0\n",
+                "github_repo_stars{repo=\"octocat/say-\\\"hi\\\"\"} 3\n",
+                "# TYPE github_repo_commits gauge\n",
+                "# HELP github_repo_commits Commits on the default branch of the repository.\n",
+                "github_repo_commits{repo=\"octocat/foo\"} 5\n",
+                "github_repo_commits{repo=\"octocat/say-\\\"hi\\\"\"} 10\n",
+                "# TYPE github_repo_commits_period gauge\n",
+                "# HELP github_repo_commits_period Commits on the default branch of the ",
+                "repository in the period of the statistics.\n",
+                "github_repo_commits_period{repo=\"octocat/foo\"} 1\n",
+                "github_repo_commits_period{repo=\"octocat/say-\\\"hi\\\"\"} 1\n",
+                "# TYPE github_language_size gauge\n",
+                "# HELP github_language_size Size of the language over the counted ",
+                "repositories, in bytes weighted by weight.\n",
+                "github_language_size{language=\"Rust\",weight=\"size\"} 300\n",
+                "# EOF\n"
+            )
+        );
+    }
+}
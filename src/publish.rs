@@ -0,0 +1,249 @@
+use crate::config::Publish;
+use crate::AppError;
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+///
+/// Run git in the working tree and return its output whether it succeeded or not.
+///
+fn git(repository: &Path, args: &[&str]) -> Result<Output, AppError> {
+    debug!("git {}", args.join(" "));
+    Command::new("git")
+        .arg("-C")
+        .arg(repository)
+        .args(args)
+        .output()
+        .map_err(|_| AppError::PublishError)
+}
+
+///
+/// Run git in the working tree and fail when it does.
+///
+fn run_git(repository: &Path, args: &[&str]) -> Result<(), AppError> {
+    let output = git(repository, args)?;
+    if !output.status.success() {
+        warn!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return Err(AppError::PublishError);
+    }
+    Ok(())
+}
+
+///
+/// Copy the files of `source` that match `files` into the working tree and commit them on the
+/// branch, then push the branch when a remote is set. Returns whether anything was committed,
+/// which is only when the copied files differ from the last commit. Only the copied files are
+/// staged and committed, and nothing is done while other changes are staged. `written` are
+/// files updated in place, such as the README, that are committed too when they are in the
+/// working tree.
+///
+pub fn publish(
+    source: &Path,
+    settings: &Publish,
+    written: &[&Path],
+    now: DateTime<Utc>,
+) -> Result<bool, AppError> {
+    let repository = Path::new(&settings.repository);
+    let branch = settings.branch.as_str();
+    let staged = git(repository, &["diff", "--cached", "--name-only"])?;
+    if !staged.status.success() || !staged.stdout.is_empty() {
+        warn!(
+            "changes are already staged in {}: {}",
+            settings.repository,
+            String::from_utf8_lossy(&staged.stdout).trim()
+        );
+        return Err(AppError::PublishError);
+    }
+    if !git(repository, &["checkout", "-q", branch])?
+        .status
+        .success()
+    {
+        run_git(repository, &["checkout", "-q", "-b", branch])?;
+    }
+
+    let destination = repository.join(&settings.path);
+    fs::create_dir_all(&destination).map_err(|_| AppError::PublishError)?;
+    let mut names: Vec<_> = fs::read_dir(source)
+        .map_err(|_| AppError::PublishError)?
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| settings.files.iter().any(|p| p.is_match(name)))
+        .collect();
+    names.sort();
+    let mut paths = Vec::new();
+    for name in &names {
+        fs::copy(source.join(name), destination.join(name)).map_err(|_| AppError::PublishError)?;
+        let path = Path::new(&settings.path).join(name);
+        paths.push(path.to_str().ok_or(AppError::PublishError)?.to_string());
+    }
+    let top = fs::canonicalize(repository).map_err(|_| AppError::PublishError)?;
+    for file in written {
+        let Ok(file) = fs::canonicalize(file) else {
+            continue;
+        };
+        if let Some(path) = file.strip_prefix(&top).ok().and_then(Path::to_str) {
+            paths.push(path.to_string());
+        }
+    }
+    if paths.is_empty() {
+        debug!("no files to publish");
+        return Ok(false);
+    }
+    let paths: Vec<_> = paths.iter().map(String::as_str).collect();
+
+    run_git(repository, &[&["add", "--"], paths.as_slice()].concat())?;
+    if git(
+        repository,
+        &[&["diff", "--cached", "--quiet", "--"], paths.as_slice()].concat(),
+    )?
+    .status
+    .success()
+    {
+        debug!("nothing to publish");
+        return Ok(false);
+    }
+
+    let message = settings
+        .message
+        .replace("{date}", &now.format("%Y-%m-%d %H:%M UTC").to_string());
+    let mut args = Vec::new();
+    if let Some(name) = &settings.author_name {
+        args.extend(["-c".to_string(), format!("user.name={}", name)]);
+    }
+    if let Some(email) = &settings.author_email {
+        args.extend(["-c".to_string(), format!("user.email={}", email)]);
+    }
+    args.extend(["commit", "-q", "-m", &message, "--"].map(String::from));
+    args.extend(paths.iter().map(|p| p.to_string()));
+    run_git(
+        repository,
+        &args.iter().map(String::as_str).collect::<Vec<_>>(),
+    )?;
+
+    if let Some(remote) = &settings.remote {
+        run_git(repository, &["push", "-q", remote, branch])?;
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn git_output(args: &[&str]) -> String {
+        let output = Command::new("git").args(args).output().unwrap();
+        assert!(output.status.success(), "git {:?}", args);
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[test]
+    fn test_publish() {
+        let base = std::env::temp_dir().join(format!("publish-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let source = base.join("source");
+        let remote = base.join("remote.git");
+        let work = base.join("work");
+        fs::create_dir_all(&source).unwrap();
+        let path = |p: &PathBuf| p.to_str().unwrap().to_string();
+        git_output(&["init", "-q", "--bare", &path(&remote)]);
+        git_output(&["clone", "-q", &path(&remote), &path(&work)]);
+
+        fs::write(source.join("github_stats.svg"), "<svg/>").unwrap();
+        fs::write(source.join("github_stats.json"), "[]").unwrap();
+        fs::write(source.join("notes.txt"), "not published").unwrap();
+        let settings: Publish = toml::from_str(&format!(
+            r#"
+            enabled = true
+            repository = "{}"
+            branch = "stats"
+            path = "images"
+            message = "Update stats ({{date}})"
+            author_name = "Octocat"
+            author_email = "octocat@example.com"
+            remote = "origin"
+            "#,
+            path(&work)
+        ))
+        .unwrap();
+        let now = "2023-08-08T00:00:00Z".parse().unwrap();
+
+        assert!(publish(&source, &settings, &[], now).unwrap());
+        let remote = path(&remote);
+        let log = |format: &str| {
+            git_output(&[
+                "--git-dir",
+                &remote,
+                "log",
+                &format!("--format={}", format),
+                "stats",
+            ])
+        };
+        assert_eq!(log("%s"), "Update stats (2023-08-08 00:00 UTC)\n");
+        assert_eq!(log("%an <%ae>"), "Octocat <octocat@example.com>\n");
+        let files = git_output(&[
+            "--git-dir",
+            &remote,
+            "ls-tree",
+            "-r",
+            "--name-only",
+            "stats",
+        ]);
+        assert_eq!(files, "images/github_stats.json\nimages/github_stats.svg\n");
+
+        assert!(!publish(&source, &settings, &[], now).unwrap());
+        fs::write(source.join("github_stats.svg"), "<svg></svg>").unwrap();
+        // an unrelated change in the working tree is left alone
+        fs::write(work.join("notes.txt"), "draft").unwrap();
+        assert!(publish(&source, &settings, &[], now).unwrap());
+        assert_eq!(log("%s").lines().count(), 2);
+        let status = git_output(&["-C", &path(&work), "status", "--porcelain"]);
+        assert_eq!(status, "?? notes.txt\n");
+
+        // nor is anything published while other changes are staged
+        git_output(&["-C", &path(&work), "add", "notes.txt"]);
+        fs::write(source.join("github_stats.svg"), "<svg />").unwrap();
+        assert!(publish(&source, &settings, &[], now).is_err());
+        assert_eq!(log("%s").lines().count(), 2);
+        git_output(&["-C", &path(&work), "reset", "-q"]);
+
+        // at the top of the working tree
+        let settings = Publish {
+            path: String::new(),
+            ..settings
+        };
+        assert!(publish(&source, &settings, &[], now).unwrap());
+        let files = git_output(&["-C", &path(&work), "ls-files"]);
+        assert_eq!(
+            files,
+            "github_stats.json\ngithub_stats.svg\nimages/github_stats.json\nimages/github_stats.svg\n"
+        );
+
+        // a README updated in the working tree goes with them, a file outside it does not
+        let readme = work.join("README.md");
+        fs::write(&readme, "# Octocat").unwrap();
+        let outside = source.join("notes.txt");
+        let written = [readme.as_path(), outside.as_path()];
+        assert!(publish(&source, &settings, &written, now).unwrap());
+        assert_eq!(log("%s").lines().count(), 4);
+        let changed = git_output(&[
+            "-C",
+            &path(&work),
+            "show",
+            "--name-only",
+            "--format=",
+            "HEAD",
+        ]);
+        assert_eq!(changed, "README.md\n");
+        assert!(!publish(&source, &settings, &written, now).unwrap());
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+use crate::AppError;
+use log::warn;
+use resvg::tiny_skia::{Pixmap, Transform};
+use resvg::usvg::{fontdb, Options, TreeParsing, TreeTextToPath};
+use svg::Document;
+
+const FONT_FAMILY: &str = "DejaVu Sans";
+const FONT_REGULAR: &[u8] = include_bytes!("../assets/fonts/DejaVuSans.ttf");
+const FONT_BOLD: &[u8] = include_bytes!("../assets/fonts/DejaVuSans-Bold.ttf");
+
+///
+/// Rasterise the card to PNG, scaling its size by `scale`.
+///
+/// Only the embedded fonts and the font files of `fallback_fonts` are used (no system fonts), so
+/// the output is the same on every machine with the same files. DejaVu Sans has no CJK glyphs, so
+/// Japanese labels need a fallback such as Noto Sans CJK, which is used for every character the
+/// embedded fonts lack.
+///
+pub fn render_png(
+    document: &Document,
+    scale: f32,
+    fallback_fonts: &[String],
+) -> Result<Vec<u8>, AppError> {
+    let mut fonts = fontdb::Database::new();
+    fonts.load_font_data(FONT_REGULAR.to_vec());
+    fonts.load_font_data(FONT_BOLD.to_vec());
+    for path in fallback_fonts {
+        let data = std::fs::read(path).map_err(|e| {
+            warn!("failed to read the font {}: {}", path, e);
+            AppError::PngOutputError
+        })?;
+        fonts.load_font_data(data);
+    }
+    fonts.set_sans_serif_family(FONT_FAMILY);
+
+    let options = Options {
+        font_family: FONT_FAMILY.to_string(),
+        ..Options::default()
+    };
+    let mut tree = resvg::usvg::Tree::from_str(&document.to_string(), &options)
+        .map_err(|_| AppError::PngOutputError)?;
+    tree.convert_text(&fonts);
+    let tree = resvg::Tree::from_usvg(&tree);
+
+    let size = tree
+        .size
+        .to_int_size()
+        .scale_by(scale)
+        .ok_or(AppError::PngOutputError)?;
+    let mut pixmap = Pixmap::new(size.width(), size.height()).ok_or(AppError::PngOutputError)?;
+    tree.render(Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|_| AppError::PngOutputError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svg::node::element::{Rectangle, Text};
+
+    fn decode_size(png: &[u8]) -> (u32, u32) {
+        let pixmap = Pixmap::decode_png(png).unwrap();
+        (pixmap.width(), pixmap.height())
+    }
+
+    #[test]
+    fn test_render_png() {
+        let document = Document::new()
+            .set("width", 100)
+            .set("height", 40)
+            .set("viewBox", "0 0 100 40")
+            .add(
+                Rectangle::new()
+                    .set("width", 50)
+                    .set("height", 40)
+                    .set("fill", "red"),
+            )
+            .add(
+                Text::new()
+                    .set("x", 60)
+                    .set("y", 20)
+                    .set("style", "font: 400 9px 'Segoe UI', Ubuntu, Sans-Serif")
+                    .add(svg::node::Text::new("Rust")),
+            );
+
+        let png = render_png(&document, 1.0, &[]).unwrap();
+        assert_eq!(decode_size(&png), (100, 40));
+        assert_eq!(png, render_png(&document, 1.0, &[]).unwrap());
+
+        let png = render_png(&document, 2.0, &[]).unwrap();
+        assert_eq!(decode_size(&png), (200, 80));
+
+        // the text must have been drawn with the embedded font
+        let pixmap = Pixmap::decode_png(&render_png(&document, 1.0, &[]).unwrap()).unwrap();
+        let drawn = (60..100)
+            .flat_map(|x| (0..40).map(move |y| (x, y)))
+            .filter(|&(x, y)| pixmap.pixel(x, y).unwrap().alpha() > 0)
+            .count();
+        assert!(drawn > 0);
+
+        let missing = ["testdata/no-such-font.ttf".to_string()];
+        assert!(render_png(&document, 1.0, &missing).is_err());
+    }
+
+    #[test]
+    fn test_render_png_ja() {
+        // no CJK font can be embedded here, so the test uses one of the system when there is one
+        let Some(font) = [
+            "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+            "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+            "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+            "/System/Library/Fonts/Hiragino Sans GB.ttc",
+        ]
+        .into_iter()
+        .find(|path| std::path::Path::new(path).exists()) else {
+            eprintln!("no CJK font is installed, skipping");
+            return;
+        };
+        let document = Document::new()
+            .set("width", 100)
+            .set("height", 40)
+            .set("viewBox", "0 0 100 40")
+            .add(
+                Text::new()
+                    .set("x", 10)
+                    .set("y", 20)
+                    .set("style", "font: 400 11px 'Segoe UI', Ubuntu, Sans-Serif")
+                    .add(svg::node::Text::new("使用言語")),
+            );
+        let png = render_png(&document, 1.0, &[font.to_string()]).unwrap();
+        assert_eq!(decode_size(&png), (100, 40));
+        // drawn with the glyphs of the fallback rather than the boxes of DejaVu Sans
+        assert_ne!(png, render_png(&document, 1.0, &[]).unwrap());
+    }
+}
@@ -0,0 +1,128 @@
+use crate::config::Readme;
+use crate::export::{self, Report};
+use crate::renderer::Renderer;
+use crate::AppError;
+use log::warn;
+use std::fs;
+
+///
+/// The language chart and the two commit charts as Markdown tables, with the same bars and
+/// percentages as the card.
+///
+pub fn render_tables(renderer: &Renderer) -> String {
+    export::markdown_tables(&Report::new(renderer))
+}
+
+///
+/// An `<img>` tag whose query string changes with `version`, so that caches such as GitHub's
+/// image proxy fetch the new image. Both attributes are escaped, `src` comes from the config.
+///
+pub fn render_image(src: &str, alt: &str, version: u64) -> String {
+    let separator = if src.contains('?') { '&' } else { '?' };
+    let src = format!("{}{}v={:x}", src, separator, version);
+    format!(
+        "<img src=\"{}\" alt=\"{}\">",
+        export::escape_html(&src),
+        export::escape_html(alt)
+    )
+}
+
+///
+/// Replace what is between `<!-- {marker}:START -->` and `<!-- {marker}:END -->` with
+/// `content`. `None` when the markers are missing or out of order.
+///
+pub fn replace_section(text: &str, marker: &str, content: &str) -> Option<String> {
+    let start_marker = format!("<!-- {}:START -->", marker);
+    let end_marker = format!("<!-- {}:END -->", marker);
+    let start = text.find(&start_marker)? + start_marker.len();
+    let end = start + text[start..].find(&end_marker)?;
+    Some(format!("{}\n{}\n{}", &text[..start], content, &text[end..]))
+}
+
+///
+/// Rewrite the sections of the Markdown file that have markers, and write it only when it
+/// changed. Returns whether it was written.
+///
+pub fn update(settings: &Readme, renderer: &Renderer, version: u64) -> Result<bool, AppError> {
+    let text = fs::read_to_string(&settings.path).map_err(|_| AppError::ReadmeError)?;
+    let mut updated = text.clone();
+    let mut found = false;
+    let alt = renderer.config.locale.messages().card_title;
+    let sections = [
+        (&settings.table_marker, render_tables(renderer)),
+        (
+            &settings.image_marker,
+            render_image(&settings.image, alt, version),
+        ),
+    ];
+    for (marker, content) in sections {
+        if let Some(replaced) = replace_section(&updated, marker, &content) {
+            updated = replaced;
+            found = true;
+        }
+    }
+    if !found {
+        warn!("no section markers in {}", settings.path);
+    }
+    if updated == text {
+        return Ok(false);
+    }
+    crate::write_atomic(&settings.path, updated).map_err(|_| AppError::ReadmeError)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_config, create_language, create_renderer, create_stat};
+
+    #[test]
+    fn test_replace_section() {
+        let text = "# Me\n<!-- STATS:START -->\nold\n<!-- STATS:END -->\nbye\n";
+        assert_eq!(
+            replace_section(text, "STATS", "new").unwrap(),
+            "# Me\n<!-- STATS:START -->\nnew\n<!-- STATS:END -->\nbye\n"
+        );
+        let replaced = replace_section(text, "STATS", "new").unwrap();
+        assert_eq!(
+            replace_section(&replaced, "STATS", "new").unwrap(),
+            replaced
+        );
+        assert_eq!(replace_section(text, "IMAGE", "new"), None);
+        let reversed = "<!-- STATS:END -->\n<!-- STATS:START -->\n";
+        assert_eq!(replace_section(reversed, "STATS", "new"), None);
+    }
+
+    #[test]
+    fn test_render_image() {
+        assert_eq!(
+            render_image("github_stats.svg", "GitHub \"Stats\"", 255),
+            "<img src=\"github_stats.svg?v=ff\" alt=\"GitHub &quot;Stats&quot;\">"
+        );
+        assert_eq!(
+            render_image("card.svg?theme=dark", "", 1),
+            "<img src=\"card.svg?theme=dark&amp;v=1\" alt=\"\">"
+        );
+        assert_eq!(
+            render_image("a\" onerror=\"x.svg?a=1&b=<2>", "", 1),
+            "<img src=\"a&quot; onerror=&quot;x.svg?a=1&amp;b=&lt;2&gt;&amp;v=1\" alt=\"\">"
+        );
+    }
+
+    #[test]
+    fn test_render_tables() {
+        let stat = crate::graphql::RepositoryStat {
+            languages: vec![create_language("Rust", "#dea584", 3000)],
+            total_commit_count: 1200,
+            period_commit_count: 3,
+            ..create_stat("foo")
+        };
+        let tables = render_tables(&create_renderer(vec![stat], create_config()));
+        let lines: Vec<_> = tables.lines().collect();
+        assert_eq!(lines[0], "| Top Languages | % |");
+        assert_eq!(lines[2], "| Rust | 100.0% |");
+        assert_eq!(lines[4], "| Top Commits | % |");
+        assert_eq!(lines[6], "| foo | 100.0% (1,200) |");
+        assert_eq!(lines[10], "| foo | 100.0% (3) |");
+    }
+}
@@ -1,38 +1,65 @@
-use crate::config;
-use chrono::Utc;
-use log::debug;
+use crate::color::{self, MIN_GRAPHIC_CONTRAST, MIN_TEXT_CONTRAST};
+use crate::config::{Config, LabelOverflow, LanguageWeight, PrivatePolicy};
+use crate::diff::DiffReport;
+use crate::font::{self, ELLIPSIS};
+use crate::i18n::{self, Locale};
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
 use std::collections::HashMap;
-use svg::node::element::{Definitions, LinearGradient, Link, Path, Rectangle, Stop, Style, Text};
+use svg::node::element::{
+    Circle, Definitions, Description, Line, LinearGradient, Link, Path, Polyline, Rectangle, Stop,
+    Style, TSpan, Text, Title,
+};
 use svg::Document;
 
 use crate::graphql::RepositoryStat;
+use crate::history::Snapshot;
 
-const CSS: &'static str = r#".chart text {
-    font: 400 9px 'Segoe UI', Ubuntu, Sans-Serif;
-}
-.title {
-    font: 600 11px 'Segoe UI', Ubuntu, Sans-Serif;
-    fill: #2f80ed;
-}
-.star path {
-    fill: #4c71f2;
-}
-.star text {
-    font: 600 14px 'Segoe UI', Ubuntu, "Helvetica Neue", Sans-Serif;
-    fill: #434d58;
-}
-.footer > text {
-    font: 200 11px 'Segoe UI', Ubuntu, Sans-Serif;
-    fill: gray;
-}
-.footer > a > text {
-    font: 200 11px 'Segoe UI', Ubuntu, Sans-Serif;
-    fill: gray;
-    text-decoration: underline;
-}"#;
+const BACKGROUND_COLOR: &str = "#ffffff";
+const TEXT_COLOR: &str = "#000000";
+pub const TITLE_COLOR: &str = "#0969da";
+const STAR_COLOR: &str = "#4c71f2";
+const STAR_TEXT_COLOR: &str = "#434d58";
+const FOOTER_COLOR: &str = "#656d76";
+pub const BAR_TRACK_COLOR: &str = "#ddd";
+pub const COMMITS_TREND_COLOR: &str = "#1f6feb";
+/// The bar of the languages of all private repositories added up.
+const PRIVATE_COLOR: &str = "#8b949e";
+/// The bar of the languages left out by the limit.
+const OTHER_COLOR: &str = "#6e7781";
+
+const DARK_BACKGROUND_COLOR: &str = "#0d1117";
+const DARK_TEXT_COLOR: &str = "#c9d1d9";
+const DARK_TITLE_COLOR: &str = "#58a6ff";
+const DARK_STAR_TEXT_COLOR: &str = "#c9d1d9";
+const DARK_FOOTER_COLOR: &str = "#8b949e";
+const DARK_BAR_TRACK_COLOR: &str = "#30363d";
 
-const STAR_ICON: &'static str = "M8 .25a.75.75 0 0 1 .673.418l1.882 3.815 4.21.612a.75.75 0 0 1 .416 1.279l-3.046 2.97.719 4.192a.751.751 0 0 1-1.088.791L8 12.347l-3.766 1.98a.75.75 0 0 1-1.088-.79l.72-4.194L.818 6.374a.75.75 0 0 1 .416-1.28l4.21-.611L7.327.668A.75.75 0 0 1 8 .25Zm0 2.445L6.615 5.5a.75.75 0 0 1-.564.41l-3.097.45 2.24 2.184a.75.75 0 0 1 .216.664l-.528 3.084 2.769-1.456a.75.75 0 0 1 .698 0l2.77 1.456-.53-3.084a.75.75 0 0 1 .216-.664l2.24-2.183-3.096-.45a.75.75 0 0 1-.564-.41L8 2.694Z";
+/// Foreground colors of the light and dark themes with their background and the contrast
+/// they need against it. The star icon keeps its color in the dark theme.
+const THEME_CONTRAST_PAIRS: [(&str, &str, f64); 10] = [
+    (TEXT_COLOR, BACKGROUND_COLOR, MIN_TEXT_CONTRAST),
+    (TITLE_COLOR, BACKGROUND_COLOR, MIN_TEXT_CONTRAST),
+    (STAR_TEXT_COLOR, BACKGROUND_COLOR, MIN_TEXT_CONTRAST),
+    (FOOTER_COLOR, BACKGROUND_COLOR, MIN_TEXT_CONTRAST),
+    (STAR_COLOR, BACKGROUND_COLOR, MIN_GRAPHIC_CONTRAST),
+    (DARK_TEXT_COLOR, DARK_BACKGROUND_COLOR, MIN_TEXT_CONTRAST),
+    (DARK_TITLE_COLOR, DARK_BACKGROUND_COLOR, MIN_TEXT_CONTRAST),
+    (
+        DARK_STAR_TEXT_COLOR,
+        DARK_BACKGROUND_COLOR,
+        MIN_TEXT_CONTRAST,
+    ),
+    (DARK_FOOTER_COLOR, DARK_BACKGROUND_COLOR, MIN_TEXT_CONTRAST),
+    (STAR_COLOR, DARK_BACKGROUND_COLOR, MIN_GRAPHIC_CONTRAST),
+];
+
+const STAR_ICON: &str = "M8 .25a.75.75 0 0 1 .673.418l1.882 3.815 4.21.612a.75.75 0 0 1 .416 1.279l-3.046 2.97.719 4.192a.751.751 0 0 1-1.088.791L8 12.347l-3.766 1.98a.75.75 0 0 1-1.088-.79l.72-4.194L.818 6.374a.75.75 0 0 1 .416-1.28l4.21-.611L7.327.668A.75.75 0 0 1 8 .25Zm0 2.445L6.615 5.5a.75.75 0 0 1-.564.41l-3.097.45 2.24 2.184a.75.75 0 0 1 .216.664l-.528 3.084 2.769-1.456a.75.75 0 0 1 .698 0l2.77 1.456-.53-3.084a.75.75 0 0 1 .216-.664l2.24-2.183-3.096-.45a.75.75 0 0 1-.564-.41L8 2.694Z";
 const CHART_WIDTH: i32 = 200;
+const LABEL_FONT_SIZE: f64 = 9.0;
+const MIN_LABEL_FONT_SIZE: f64 = 6.0;
+/// Fonts appended to every font stack so that CJK labels do not fall back to tofu.
+const CJK_FONTS: &str = "'Hiragino Sans', 'Noto Sans CJK JP', 'Yu Gothic', Meiryo";
 
 #[derive(Debug)]
 pub struct LanguageSummary {
@@ -40,13 +67,52 @@ pub struct LanguageSummary {
     pub data: HashMap<String, LanguageSummaryValue>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LanguageSummaryValue {
     pub name: String,
     pub color: String,
     pub size: i64,
 }
 
+///
+/// The repositories tagged with a topic and their commits
+///
+#[derive(Debug, PartialEq)]
+pub struct TopicSummary {
+    pub name: String,
+    pub repository_count: i64,
+    pub commit_count: i64,
+}
+
+///
+/// A bar of the commit charts: one repository, or the repositories it stands for added up
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepositoryRow {
+    pub name: String,
+    pub kind: RowKind,
+    pub stargazer_count: i64,
+    pub total_commit_count: i64,
+    pub period_commit_count: i64,
+}
+
+///
+/// What a row of the commit charts stands for
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowKind {
+    /// A repository shown under its own name.
+    Repository(Box<RepositoryStat>),
+    /// A private repository shown as "private repo #n", so nothing else of it is kept.
+    Anonymized,
+    /// All private repositories.
+    Private,
+    /// The repositories tagged with a topic of a project.
+    Project,
+    /// The repositories left out by the limit.
+    Other,
+}
+
 #[derive(Debug)]
 pub struct RepositorySummary {
     pub total_commit_count: i64,
@@ -54,6 +120,16 @@ pub struct RepositorySummary {
     pub star_count: i64,
 }
 
+///
+/// The text drawn above a bar, fitted to the chart width
+///
+#[derive(Debug, PartialEq)]
+pub struct BarLabel {
+    pub text: String,
+    pub title: Option<String>,
+    pub font_size: Option<f64>,
+}
+
 impl LanguageSummary {
     pub fn new() -> Self {
         let data: HashMap<String, LanguageSummaryValue> = HashMap::new();
@@ -62,6 +138,68 @@ impl LanguageSummary {
             data,
         }
     }
+
+    ///
+    /// The `count` largest languages, largest first and by name among equal sizes.
+    ///
+    pub fn top(&self, count: usize) -> Vec<&LanguageSummaryValue> {
+        let mut values: Vec<_> = self.data.values().collect();
+        values.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        values.truncate(count);
+        values
+    }
+
+    ///
+    /// Count `size` for a language after applying `language_mapping` and `rename_language`.
+    /// The color of linguist is preferred over `color`. Ignored languages are not counted.
+    ///
+    pub fn add(
+        &mut self,
+        name: &str,
+        color: &str,
+        size: i64,
+        language_colors: &HashMap<String, String>,
+        language_groups: &HashMap<String, String>,
+        config: &Config,
+    ) {
+        if config.ignore_languages.iter().any(|l| l == name) {
+            return;
+        }
+        self.total_size += size;
+        let lang_name = match config.language_mapping.get(name) {
+            Some(name) => name,
+            None => name,
+        };
+        let group = config
+            .language_groups
+            .iter()
+            .find(|g| g.languages.iter().any(|p| p.is_match(lang_name)));
+        let (lang_name, group_color) = match group {
+            Some(group) => (group.name.as_str(), group.color.as_deref()),
+            None => match language_groups.get(lang_name) {
+                Some(group) if config.linguist_groups => (group.as_str(), None),
+                _ => (lang_name, None),
+            },
+        };
+        let color = match (group_color, language_colors.get(lang_name)) {
+            (Some(c), _) => c,
+            (None, Some(c)) => c,
+            (None, None) => color,
+        };
+        let renamed = match config.rename_language.get(lang_name) {
+            Some(name) => name,
+            None => lang_name,
+        };
+        let entry = self
+            .data
+            .entry(renamed.to_string())
+            .or_insert(LanguageSummaryValue {
+                name: renamed.to_string(),
+                color: color.to_string(),
+                size: 0,
+            });
+        entry.size += size;
+    }
 }
 
 impl RepositorySummary {
@@ -74,120 +212,969 @@ impl RepositorySummary {
     }
 }
 
+impl RepositoryRow {
+    pub fn new(name: String, kind: RowKind) -> Self {
+        Self {
+            name,
+            kind,
+            stargazer_count: 0,
+            total_commit_count: 0,
+            period_commit_count: 0,
+        }
+    }
+
+    fn add(&mut self, stat: &RepositoryStat) {
+        self.stargazer_count += stat.stargazer_count;
+        self.total_commit_count += stat.total_commit_count;
+        self.period_commit_count += stat.period_commit_count;
+    }
+}
+
+impl BarLabel {
+    ///
+    /// Build the label `"{name}: {detail}"`, fitting it into the chart width.
+    /// Only the name is shortened so that the numbers always stay readable.
+    ///
+    pub fn fit(name: &str, detail: &str, overflow: LabelOverflow) -> Self {
+        let full = format!("{}: {}", name, detail);
+        let max_width = CHART_WIDTH as f64;
+        let width = font::measure_text(&full, LABEL_FONT_SIZE);
+        if width <= max_width {
+            return BarLabel::from(full.as_str());
+        }
+
+        let mut font_size = LABEL_FONT_SIZE;
+        if overflow == LabelOverflow::Shrink {
+            font_size = (LABEL_FONT_SIZE * max_width / width).max(MIN_LABEL_FONT_SIZE);
+            if font::measure_text(&full, font_size) <= max_width {
+                return Self {
+                    text: full,
+                    title: None,
+                    font_size: Some(font_size),
+                };
+            }
+        }
+
+        let text = truncate(name, &format!(": {}", detail), font_size, max_width);
+        Self {
+            text,
+            title: Some(full),
+            font_size: if font_size < LABEL_FONT_SIZE {
+                Some(font_size)
+            } else {
+                None
+            },
+        }
+    }
+
+    ///
+    /// Replace the tooltip, e.g. with the exact values behind a rounded label.
+    ///
+    pub fn with_title(mut self, title: String) -> Self {
+        self.title = Some(title);
+        self
+    }
+}
+
+impl From<&str> for BarLabel {
+    fn from(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            title: None,
+            font_size: None,
+        }
+    }
+}
+
+///
+/// A part of the card that can also be written as a standalone image
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Panel {
+    Header,
+    Languages,
+    Commits,
+    ActiveCommits,
+    Topics,
+}
+
+impl Panel {
+    pub const ALL: [Panel; 5] = [
+        Panel::Header,
+        Panel::Languages,
+        Panel::Commits,
+        Panel::ActiveCommits,
+        Panel::Topics,
+    ];
+
+    /// The panels drawn on the combined card.
+    pub const CARD: [Panel; 4] = [
+        Panel::Header,
+        Panel::Languages,
+        Panel::Commits,
+        Panel::ActiveCommits,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Panel::Header => "header",
+            Panel::Languages => "languages",
+            Panel::Commits => "commits",
+            Panel::ActiveCommits => "active_commits",
+            Panel::Topics => "topics",
+        }
+    }
+}
+
+///
+/// The colors of a card. Light is what `create_css` draws, dark overrides it.
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ColorScheme {
+    #[default]
+    Light,
+    Dark,
+}
+
+///
+/// A chart drawn from the history of snapshots instead of the latest statistics
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trend {
+    Stars,
+    Commits,
+    Languages,
+}
+
+impl Trend {
+    pub const ALL: [Trend; 3] = [Trend::Stars, Trend::Commits, Trend::Languages];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Trend::Stars => "stars",
+            Trend::Commits => "commits",
+            Trend::Languages => "languages",
+        }
+    }
+}
+
+///
+/// One line of a trend chart
+///
+#[derive(Debug)]
+struct Series {
+    name: String,
+    color: String,
+    points: Vec<(DateTime<Utc>, f64)>,
+}
+
 #[derive(Debug)]
 pub struct Renderer {
     pub stats: Vec<RepositoryStat>,
     pub language_summary: LanguageSummary,
     pub language_colors: HashMap<String, String>,
+    /// Language to linguist group, used when `linguist_groups` is set.
+    pub language_groups: HashMap<String, String>,
     pub repository_summary: RepositorySummary,
+    /// The start of the period of `period_commit_count`, which ends at `now`.
+    pub from: DateTime<Utc>,
+    /// The time the statistics are counted at and the footer is dated with.
+    pub now: DateTime<Utc>,
+    pub config: Config,
 }
 
 impl Renderer {
-    pub fn new(stats: Vec<RepositoryStat>, language_colors: HashMap<String, String>) -> Self {
-        let config = config::load();
-        let mut language_summary = LanguageSummary::new();
-        let mut repository_summary = RepositorySummary::new();
-        let map = &mut language_summary.data;
-        let star_count = stats.iter().map(|item| item.stargazer_count).sum::<i64>();
-        repository_summary.star_count = star_count;
-        let stats: Vec<_> = stats
-            .into_iter()
-            .filter(|s| !config.ignore_repositories.contains(&s.name))
-            .collect();
-        for s in &stats {
-            debug!("{}, {}", s.name, s.period_commit_count);
-            repository_summary.total_commit_count += s.total_commit_count;
-            repository_summary.total_active_commit_count += s.period_commit_count;
-            let ls = &s.languages;
-            for l in ls {
-                if config.ignore_languages.contains(&l.name) {
-                    continue;
-                }
-                language_summary.total_size += l.size;
-                let mapped_lang = config.language_mapping.get(&l.name);
-                let lang_name = match mapped_lang {
-                    Some(name) => name,
-                    None => &l.name,
-                };
-                let color = language_colors.get(lang_name);
-                let color = match color {
-                    Some(c) => c,
-                    None => &l.color,
-                };
-                let renamed = config.rename_language.get(lang_name);
-                let renamed = match renamed {
-                    Some(name) => name,
-                    None => lang_name,
-                };
-                let mut entry = map
-                    .entry(renamed.to_string())
-                    .or_insert(LanguageSummaryValue {
-                        name: renamed.to_string(),
-                        color: color.clone(),
-                        size: 0,
-                    });
-                (*entry).size += l.size;
-            }
-        }
+    ///
+    /// Build a renderer whose output depends only on its arguments: `config` instead of
+    /// `config.toml`, and the `(from, now)` the statistics were fetched for instead of the
+    /// time it runs at.
+    ///
+    pub fn with_config(
+        stats: Vec<RepositoryStat>,
+        language_colors: HashMap<String, String>,
+        language_groups: HashMap<String, String>,
+        config: Config,
+        period: (DateTime<Utc>, DateTime<Utc>),
+    ) -> Self {
+        let (from, now) = period;
+        let (stats, language_summary, repository_summary) =
+            summarize(stats, &language_colors, &language_groups, &config, now);
         debug!("repository_summary: {:?}", repository_summary);
         Self {
             stats,
             language_summary,
             language_colors,
+            language_groups,
             repository_summary,
+            from,
+            now,
+            config,
         }
     }
 
+    ///
+    /// The days of the period of `period_commit_count`.
+    ///
+    pub fn period_days(&self) -> i64 {
+        (self.now - self.from).num_days()
+    }
+
     pub fn render(&mut self) -> Document {
-        let styles = Style::new(CSS);
-        let header_pane = create_header_pane(self.repository_summary.star_count, 20, 10);
+        let config = &self.config;
+        if config.theme.check_contrast {
+            check_theme_contrast();
+        }
+        let locale = config.locale;
+        let description = Panel::CARD
+            .iter()
+            .map(|panel| self.create_description(*panel))
+            .collect::<Vec<_>>()
+            .join(locale.messages().sentence_separator);
+        let header_pane = create_header_pane(self.repository_summary.star_count, locale, 20, 10);
         let top_langs_chart = self.create_top_langs_chart(20, 30);
         let top_commits_chart = self.create_top_commits_chart(240, 30);
         let top_active_commits_chart = self.create_top_active_commits_chart(460, 30);
-        let footer_pane = create_footer_pane(20, 500);
-
-        let defs = Definitions::new()
-            .add(create_gradient("green-grad", "#66ff66", "#009900"))
-            .add(create_gradient("blue-grad", "#66ccff", "#0000ff"));
-
-        let root = Document::new()
-            .set("width", 660)
-            .set("height", 540)
-            .set("viewBox", "0 0 660 540")
-            .add(styles)
-            .add(defs)
+        let footer_pane = create_footer_pane(locale, self.now, 20, 500);
+
+        create_card("card", 660, 540, locale.messages().card_title, &description)
             .add(header_pane)
             .add(top_langs_chart)
             .add(top_commits_chart)
             .add(top_active_commits_chart)
-            .add(footer_pane);
+            .add(footer_pane)
+    }
 
-        root
+    ///
+    /// Render one panel of the card as a standalone image sized to its content.
+    ///
+    pub fn render_panel(&mut self, panel: Panel) -> Document {
+        const PANEL_WIDTH: i32 = CHART_WIDTH + 40;
+        let config = &self.config;
+        let locale = config.locale;
+        let messages = locale.messages();
+        let description = self.create_description(panel);
+        let id = panel.name();
+        // a chart is its title followed by 40px per bar, see `create_bar_chart`
+        let chart_height = |bars: usize| 60 + 40 * bars.max(1) as i32;
+        match panel {
+            Panel::Header => {
+                let header_pane =
+                    create_header_pane(self.repository_summary.star_count, locale, 20, 10);
+                create_card(id, 300, 36, messages.card_title, &description).add(header_pane)
+            }
+            Panel::Languages => {
+                let height = chart_height(self.shown_languages().len());
+                create_card(
+                    id,
+                    PANEL_WIDTH,
+                    height,
+                    languages_title(config),
+                    &description,
+                )
+                .add(self.create_top_langs_chart(20, 0))
+            }
+            Panel::Commits => {
+                let height = chart_height(self.shown_commit_repositories().len());
+                create_card(id, PANEL_WIDTH, height, messages.top_commits, &description)
+                    .add(self.create_top_commits_chart(20, 0))
+            }
+            Panel::ActiveCommits => {
+                let height = chart_height(self.shown_active_commit_repositories().len());
+                create_card(
+                    id,
+                    PANEL_WIDTH,
+                    height,
+                    &locale.fill_period(messages.top_active_commits, self.period_days()),
+                    &description,
+                )
+                .add(self.create_top_active_commits_chart(20, 0))
+            }
+            Panel::Topics => {
+                let height =
+                    chart_height(self.top_topics(config.limit(config.limits.topics)).len());
+                create_card(id, PANEL_WIDTH, height, messages.top_topics, &description)
+                    .add(self.create_top_topics_chart(20, 0))
+            }
+        }
+    }
+
+    ///
+    /// Render the language chart as one stacked bar with a legend in two columns.
+    ///
+    pub fn render_compact_languages(&self) -> Document {
+        const COLUMN_WIDTH: i32 = CHART_WIDTH / 2;
+        let config = &self.config;
+        let locale = config.locale;
+        let values = self.shown_languages();
+        let total = values.iter().map(|d| d.size).sum::<i64>().max(1);
+
+        let mut bar = Document::new()
+            .set("y", 40)
+            .set("width", CHART_WIDTH)
+            .set("height", 8);
+        let mut legend = Document::new().set("y", 60).set("class", "chart");
+        let mut offset = 0.0;
+        for (i, d) in values.iter().enumerate() {
+            let width = d.size as f64 / total as f64 * CHART_WIDTH as f64;
+            bar = bar.add(
+                Rectangle::new()
+                    .set("x", format!("{:.2}", offset))
+                    .set("width", format!("{:.2}", width))
+                    .set("height", 8)
+                    .set("fill", d.color.as_str()),
+            );
+            offset += width;
+
+            let x = (i as i32 % 2) * COLUMN_WIDTH;
+            let y = (i as i32 / 2) * 16;
+            let text = format!(
+                "{} {}%",
+                d.name,
+                locale.format_decimal(d.size as f64 / total as f64 * 100.0, 1)
+            );
+            legend = legend
+                .add(
+                    Circle::new()
+                        .set("cx", x + 4)
+                        .set("cy", y + 6)
+                        .set("r", 4)
+                        .set("fill", d.color.as_str()),
+                )
+                .add(
+                    Text::new()
+                        .set("x", x + 12)
+                        .set("y", y + 9)
+                        .add(svg::node::Text::new(fit_text(
+                            &text,
+                            (COLUMN_WIDTH - 14) as f64,
+                        ))),
+                );
+        }
+        let rows = (values.len() as i32 + 1) / 2;
+        let chart = Document::new()
+            .set("x", 20)
+            .set("y", 0)
+            .add(create_chart_title(languages_title(config), 0, 30))
+            .add(bar)
+            .add(legend);
+
+        create_card(
+            "languages-compact",
+            CHART_WIDTH + 40,
+            70 + rows * 16,
+            languages_title(config),
+            &self.create_description(Panel::Languages),
+        )
+        .add(chart)
+    }
+
+    ///
+    /// Render a trend chart from the snapshots of the history, oldest first.
+    ///
+    pub fn render_trend(&self, history: &[Snapshot], trend: Trend) -> Document {
+        const TREND_LANGUAGES_COUNT: usize = 5;
+        const LINE_CHART_HEIGHT: i32 = 80;
+        let config = &self.config;
+        let locale = config.locale;
+        let messages = locale.messages();
+        let summaries: Vec<_> = history
+            .iter()
+            .map(|s| {
+                let (_, languages, repositories) = summarize(
+                    s.stats.clone(),
+                    &self.language_colors,
+                    &self.language_groups,
+                    config,
+                    s.timestamp,
+                );
+                (s.timestamp, languages, repositories)
+            })
+            .collect();
+
+        let (title, series) = match trend {
+            Trend::Stars => (
+                messages.stars_trend,
+                vec![Series {
+                    name: messages.stars_trend.to_string(),
+                    color: STAR_COLOR.to_string(),
+                    points: summaries
+                        .iter()
+                        .map(|(t, _, r)| (*t, r.star_count as f64))
+                        .collect(),
+                }],
+            ),
+            Trend::Commits => (
+                messages.commits_trend,
+                vec![Series {
+                    name: messages.commits_trend.to_string(),
+                    color: COMMITS_TREND_COLOR.to_string(),
+                    points: summaries
+                        .iter()
+                        .map(|(t, _, r)| (*t, r.total_commit_count as f64))
+                        .collect(),
+                }],
+            ),
+            Trend::Languages => (
+                messages.languages_trend,
+                self.top_languages(
+                    TREND_LANGUAGES_COUNT.min(config.limit(config.limits.languages)),
+                )
+                .into_iter()
+                .map(|d| Series {
+                    name: d.name.clone(),
+                    color: d.color.clone(),
+                    points: summaries
+                        .iter()
+                        .map(|(t, l, _)| {
+                            let size = l.data.get(&d.name).map_or(0, |v| v.size);
+                            let share = if l.total_size > 0 {
+                                size as f64 / l.total_size as f64 * 100.0
+                            } else {
+                                0.0
+                            };
+                            (*t, share)
+                        })
+                        .collect(),
+                })
+                .collect(),
+            ),
+        };
+
+        let format_value = |v: f64| match trend {
+            Trend::Languages => format!("{}%", locale.format_decimal(v, 1)),
+            _ => locale.format_number(v as i64),
+        };
+        let values = series
+            .iter()
+            .filter_map(|s| {
+                let first = format_value(s.points.first()?.1);
+                let last = format_value(s.points.last()?.1);
+                Some(match trend {
+                    Trend::Languages => format!("{} {} → {}", s.name, first, last),
+                    _ => format!("{} → {}", first, last),
+                })
+            })
+            .collect::<Vec<_>>();
+        let (from, to) = match (history.first(), history.last()) {
+            (Some(first), Some(last)) => (
+                locale.format_date(&first.timestamp),
+                locale.format_date(&last.timestamp),
+            ),
+            _ => ("-".to_string(), "-".to_string()),
+        };
+        let values = if values.is_empty() {
+            "-".to_string()
+        } else {
+            values.join(messages.list_separator)
+        };
+        let description = i18n::fill(
+            messages.trend_description,
+            &[
+                ("title", title),
+                ("from", &from),
+                ("to", &to),
+                ("values", &values),
+            ],
+        );
+
+        let mut chart = Document::new()
+            .set("x", 20)
+            .set("y", 0)
+            .add(create_chart_title(title, 0, 30));
+        let height = match trend {
+            Trend::Languages => {
+                let line_chart =
+                    create_line_chart(&series, true, CHART_WIDTH, LINE_CHART_HEIGHT).set("y", 45);
+                let mut legend = Document::new().set("y", 140).set("class", "chart");
+                for (i, s) in series.iter().enumerate() {
+                    let last = s.points.last().map_or(0.0, |p| p.1);
+                    let y = i as i32 * 14;
+                    legend =
+                        legend
+                            .add(
+                                Rectangle::new()
+                                    .set("x", 0)
+                                    .set("y", y + 2)
+                                    .set("width", 8)
+                                    .set("height", 8)
+                                    .set("fill", s.color.as_str()),
+                            )
+                            .add(Text::new().set("x", 12).set("y", y + 10).add(
+                                svg::node::Text::new(format!("{}: {}", s.name, format_value(last))),
+                            ));
+                }
+                chart = chart.add(line_chart).add(legend);
+                150 + series.len() as i32 * 14
+            }
+            _ => {
+                let points = &series[0].points;
+                let summary = match (points.first(), points.last()) {
+                    (Some(first), Some(last)) => {
+                        let delta = (last.1 - first.1) as i64;
+                        let sign = if delta < 0 { "-" } else { "+" };
+                        format!(
+                            "{} ({}{})",
+                            format_value(last.1),
+                            sign,
+                            locale.format_number(delta.abs())
+                        )
+                    }
+                    _ => "-".to_string(),
+                };
+                let value = Document::new().set("class", "chart").add(
+                    Text::new()
+                        .set("x", 0)
+                        .set("y", 45)
+                        .add(svg::node::Text::new(summary)),
+                );
+                let line_chart =
+                    create_line_chart(&series, false, CHART_WIDTH, LINE_CHART_HEIGHT).set("y", 55);
+                chart = chart.add(value).add(line_chart);
+                145
+            }
+        };
+
+        create_card(
+            &format!("trend_{}", trend.name()),
+            CHART_WIDTH + 40,
+            height,
+            title,
+            &description,
+        )
+        .add(chart)
+    }
+
+    ///
+    /// Render the cumulative count of stars over time and the stars each repository gained
+    /// between `from` and `to`.
+    ///
+    pub fn render_star_history(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Document {
+        const LINE_CHART_HEIGHT: i32 = 80;
+        let config = &self.config;
+        let locale = config.locale;
+        let messages = locale.messages();
+
+        let mut starred_at: Vec<_> = self.stats.iter().flat_map(|r| &r.starred_at).collect();
+        starred_at.sort();
+        // one point per day is enough at this size and keeps the polyline short
+        let mut points: Vec<(DateTime<Utc>, f64)> = Vec::new();
+        for (i, t) in starred_at.iter().enumerate() {
+            let point = (**t, (i + 1) as f64);
+            match points.last_mut() {
+                Some(last) if last.0.date_naive() == t.date_naive() => *last = point,
+                _ => points.push(point),
+            }
+        }
+        if let Some(&(_, count)) = points.last() {
+            points.push((to, count));
+        }
+        let series = Series {
+            name: messages.star_history.to_string(),
+            color: STAR_COLOR.to_string(),
+            points,
+        };
+
+        let in_period = |t: &&DateTime<Utc>| **t >= from && **t < to;
+        let count = starred_at.len() as i64;
+        let gained = starred_at.iter().copied().filter(in_period).count() as i64;
+        let mut repositories: Vec<_> = self
+            .stats
+            .iter()
+            .filter(|r| !r.is_private)
+            .map(|r| {
+                let gained = r.starred_at.iter().filter(in_period).count();
+                (r, gained as i64)
+            })
+            .filter(|(_, gained)| *gained > 0)
+            .collect();
+        repositories.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.name.cmp(&b.0.name)));
+        repositories.truncate(config.limit(config.limits.commits));
+
+        let signed = |n: i64| format!("+{}", locale.format_number(n));
+        let days = (to - from).num_days();
+        let summary = i18n::fill(
+            &locale.fill_period(messages.star_history_summary, days),
+            &[
+                ("count", &locale.format_number(count)),
+                ("delta", &signed(gained)),
+            ],
+        );
+        let listed = repositories
+            .iter()
+            .map(|(r, gained)| format!("{} {}", r.name, signed(*gained)))
+            .collect::<Vec<_>>();
+        let description = i18n::fill(
+            messages.star_history_description,
+            &[
+                ("count", &locale.format_number(count)),
+                ("gained", &locale.format_number(gained)),
+                ("from", &locale.format_date(&from)),
+                ("to", &locale.format_date(&to)),
+                (
+                    "repositories",
+                    &if listed.is_empty() {
+                        "-".to_string()
+                    } else {
+                        listed.join(messages.list_separator)
+                    },
+                ),
+            ],
+        );
+
+        let value = Document::new().set("class", "chart").add(
+            Text::new()
+                .set("x", 0)
+                .set("y", 45)
+                .add(svg::node::Text::new(summary)),
+        );
+        let line_chart =
+            create_line_chart(&[series], true, CHART_WIDTH, LINE_CHART_HEIGHT).set("y", 55);
+        let mut chart = Document::new()
+            .set("x", 20)
+            .set("y", 0)
+            .add(create_chart_title(messages.star_history, 0, 30))
+            .add(value)
+            .add(line_chart);
+
+        let mut height = 145;
+        if !repositories.is_empty() {
+            let mut bars = Document::new()
+                .set("x", 0)
+                .set("y", 185)
+                .set("class", "chart");
+            for (i, (r, repository_gained)) in repositories.iter().enumerate() {
+                let detail = format!(
+                    "{} ({})",
+                    signed(*repository_gained),
+                    locale.format_number(r.stargazer_count)
+                );
+                let label = BarLabel::fit(&r.name, &detail, config.theme.label_overflow);
+                let bar = create_bar_chart(
+                    label,
+                    *repository_gained as f64 / gained as f64 * 100.0,
+                    STAR_COLOR,
+                );
+                bars = bars.add(bar.set("y", i * 40));
+            }
+            chart = chart
+                .add(create_chart_title(
+                    &locale.fill_period(messages.stars_gained, days),
+                    0,
+                    170,
+                ))
+                .add(bars);
+            height = 185 + 40 * repositories.len() as i32;
+        }
+
+        create_card(
+            "star_history",
+            CHART_WIDTH + 40,
+            height,
+            messages.star_history,
+            &description,
+        )
+        .add(chart)
+    }
+
+    ///
+    /// Summarise a panel in plain text for screen readers.
+    ///
+    fn create_description(&self, panel: Panel) -> String {
+        let config = &self.config;
+        let locale = config.locale;
+        let messages = locale.messages();
+        match panel {
+            Panel::Header => i18n::fill(
+                messages.header_description,
+                &[(
+                    "stars",
+                    &locale.format_number(self.repository_summary.star_count),
+                )],
+            ),
+            Panel::Languages => {
+                let languages = self
+                    .shown_languages()
+                    .iter()
+                    .map(|d| {
+                        format!(
+                            "{} {}%",
+                            d.name,
+                            locale.format_decimal(
+                                d.size as f64 / self.language_summary.total_size as f64 * 100.0,
+                                1
+                            )
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                let message = match config.language_weight {
+                    LanguageWeight::Size => messages.languages_description,
+                    _ => messages.recent_languages_description,
+                };
+                i18n::fill(
+                    message,
+                    &[("languages", &languages.join(messages.list_separator))],
+                )
+            }
+            Panel::Commits => {
+                let commits = self
+                    .shown_commit_repositories()
+                    .iter()
+                    .map(|r| format!("{} {}", r.name, locale.format_number(r.total_commit_count)))
+                    .collect::<Vec<_>>();
+                i18n::fill(
+                    messages.commits_description,
+                    &[("commits", &commits.join(messages.list_separator))],
+                )
+            }
+            Panel::ActiveCommits => {
+                let commits = self
+                    .shown_active_commit_repositories()
+                    .iter()
+                    .map(|r| format!("{} {}", r.name, locale.format_number(r.period_commit_count)))
+                    .collect::<Vec<_>>();
+                i18n::fill(
+                    &locale.fill_period(messages.active_commits_description, self.period_days()),
+                    &[("commits", &commits.join(messages.list_separator))],
+                )
+            }
+            Panel::Topics => {
+                let topics = self
+                    .top_topics(config.limit(config.limits.topics))
+                    .iter()
+                    .map(|t| format!("{} ({})", t.name, topic_detail(t, locale)))
+                    .collect::<Vec<_>>();
+                i18n::fill(
+                    messages.topics_description,
+                    &[("topics", &topics.join(messages.list_separator))],
+                )
+            }
+        }
+    }
+
+    pub fn top_languages(&self, count: usize) -> Vec<&LanguageSummaryValue> {
+        self.language_summary.top(count)
+    }
+
+    ///
+    /// The bars of the language chart: the top languages and, if enabled, the rest as "Other".
+    ///
+    pub fn shown_languages(&self) -> Vec<LanguageSummaryValue> {
+        let config = &self.config;
+        let mut values: Vec<_> = self
+            .top_languages(config.limit(config.limits.languages))
+            .into_iter()
+            .cloned()
+            .collect();
+        let shown = values.iter().map(|d| d.size).sum();
+        if let Some(size) = other_size(config, self.language_summary.total_size, shown) {
+            values.push(LanguageSummaryValue {
+                name: config.locale.messages().other.to_string(),
+                color: OTHER_COLOR.to_string(),
+                size,
+            });
+        }
+        values
+    }
+
+    pub fn shown_commit_repositories(&self) -> Vec<RepositoryRow> {
+        let config = &self.config;
+        let mut values = self.top_commit_repositories(config.limit(config.limits.commits));
+        let shown = values.iter().map(|r| r.total_commit_count).sum();
+        let total = self.repository_summary.total_commit_count;
+        if let Some(total_commit_count) = other_size(config, total, shown) {
+            let name = config.locale.messages().other.to_string();
+            values.push(RepositoryRow {
+                total_commit_count,
+                ..RepositoryRow::new(name, RowKind::Other)
+            });
+        }
+        values
+    }
+
+    pub fn shown_active_commit_repositories(&self) -> Vec<RepositoryRow> {
+        let config = &self.config;
+        let mut values =
+            self.top_active_commit_repositories(config.limit(config.limits.active_commits));
+        let shown = values.iter().map(|r| r.period_commit_count).sum();
+        let total = self.repository_summary.total_active_commit_count;
+        if let Some(period_commit_count) = other_size(config, total, shown) {
+            let name = config.locale.messages().other.to_string();
+            values.push(RepositoryRow {
+                period_commit_count,
+                ..RepositoryRow::new(name, RowKind::Other)
+            });
+        }
+        values
+    }
+
+    ///
+    /// The rows a panel shows under `policy`: private repositories renamed or added up, and
+    /// the repositories of a project merged into one row placed where the first of them was.
+    ///
+    pub fn population(&self, policy: PrivatePolicy) -> Vec<RepositoryRow> {
+        let config = &self.config;
+        let messages = config.locale.messages();
+        let mut values: Vec<RepositoryRow> = Vec::new();
+        let mut projects: HashMap<&str, usize> = HashMap::new();
+        let mut private: Option<RepositoryRow> = None;
+        let mut private_count = 0;
+        for s in &self.stats {
+            let row = match policy {
+                _ if !s.is_private => {
+                    RepositoryRow::new(s.name.clone(), RowKind::Repository(Box::new(s.clone())))
+                }
+                PrivatePolicy::Include => {
+                    RepositoryRow::new(s.name.clone(), RowKind::Repository(Box::new(s.clone())))
+                }
+                PrivatePolicy::Anonymize => {
+                    private_count += 1;
+                    let name = i18n::fill(
+                        messages.private_repository,
+                        &[("n", &private_count.to_string())],
+                    );
+                    RepositoryRow::new(name, RowKind::Anonymized)
+                }
+                PrivatePolicy::Aggregate => {
+                    private
+                        .get_or_insert_with(|| {
+                            let name = messages.private_repositories.to_string();
+                            RepositoryRow::new(name, RowKind::Private)
+                        })
+                        .add(s);
+                    continue;
+                }
+                PrivatePolicy::Exclude => continue,
+            };
+            let project = config
+                .projects
+                .iter()
+                .find(|(_, topics)| s.topics.iter().any(|t| topics.contains(t)));
+            let index = match project {
+                Some((name, _)) => *projects.entry(name).or_insert_with(|| {
+                    values.push(RepositoryRow::new(name.clone(), RowKind::Project));
+                    values.len() - 1
+                }),
+                None => {
+                    values.push(row);
+                    values.len() - 1
+                }
+            };
+            values[index].add(s);
+        }
+        values.extend(private);
+        values
+    }
+
+    ///
+    /// Count the repositories and commits of every topic, most commits first.
+    ///
+    pub fn top_topics(&self, count: usize) -> Vec<TopicSummary> {
+        let policy = self.config.private_repositories.topics;
+        let mut topics: HashMap<&str, TopicSummary> = HashMap::new();
+        let stats = self
+            .stats
+            .iter()
+            .filter(|s| !s.is_private || policy != PrivatePolicy::Exclude);
+        for s in stats {
+            for topic in &s.topics {
+                let entry = topics.entry(topic).or_insert(TopicSummary {
+                    name: topic.clone(),
+                    repository_count: 0,
+                    commit_count: 0,
+                });
+                entry.repository_count += 1;
+                entry.commit_count += s.total_commit_count;
+            }
+        }
+        let mut values: Vec<_> = topics.into_values().collect();
+        values.sort_by(|a, b| {
+            (b.commit_count, b.repository_count, &a.name).cmp(&(
+                a.commit_count,
+                a.repository_count,
+                &b.name,
+            ))
+        });
+        values.truncate(count);
+        values
+    }
+
+    pub fn top_commit_repositories(&self, count: usize) -> Vec<RepositoryRow> {
+        let policy = self.config.private_repositories.commits;
+        let mut values = self.population(policy);
+        values.sort_by(|a, b| {
+            b.total_commit_count
+                .cmp(&a.total_commit_count)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        values.truncate(count);
+        values
+    }
+
+    pub fn top_active_commit_repositories(&self, count: usize) -> Vec<RepositoryRow> {
+        let policy = self.config.private_repositories.active_commits;
+        let mut values: Vec<_> = self
+            .population(policy)
+            .into_iter()
+            .filter(|item| item.period_commit_count > 0)
+            .collect();
+        values.sort_by(|a, b| {
+            b.period_commit_count
+                .cmp(&a.period_commit_count)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        values.truncate(count);
+        values
     }
 
     fn create_top_langs_chart(&self, x: i32, y: i32) -> Document {
-        let config = config::load();
+        let config = &self.config;
+        let locale = config.locale;
         let mut root = Document::new();
         let mut chart = Document::new()
             .set("x", 0)
             .set("y", 50)
             .set("class", "chart");
-        let mut values: Vec<_> = self.language_summary.data.values().collect();
-        values.sort_by(|a, b| (*b).size.partial_cmp(&a.size).unwrap());
-        values.truncate(config.languages_count);
+        let values = self.shown_languages();
+        if config.theme.check_contrast {
+            check_language_contrast(&values.iter().collect::<Vec<_>>());
+        }
 
         let bars: Vec<_> = values
             .into_iter()
             .enumerate()
             .map(|(i, d)| {
-                let text = format!(
-                    "{}: {:.1}% ({}KB)",
-                    d.name.as_str(),
-                    d.size as f64 / self.language_summary.total_size as f64 * 100.0,
-                    d.size / 1000
-                );
+                let percentage = |precision| {
+                    locale.format_decimal(
+                        d.size as f64 / self.language_summary.total_size as f64 * 100.0,
+                        precision,
+                    )
+                };
+                // weighted sizes are not bytes, so only the share is shown for them
+                let (detail, title) = match config.language_weight {
+                    LanguageWeight::Size => (
+                        format!(
+                            "{}% ({}KB)",
+                            percentage(1),
+                            locale.format_number(d.size / 1000)
+                        ),
+                        i18n::fill(
+                            locale.messages().bytes_tooltip,
+                            &[
+                                ("name", &d.name),
+                                ("share", &percentage(2)),
+                                ("bytes", &locale.format_number(d.size)),
+                            ],
+                        ),
+                    ),
+                    _ => (
+                        format!("{}%", percentage(1)),
+                        format!("{}: {}%", d.name, percentage(2)),
+                    ),
+                };
+                let label =
+                    BarLabel::fit(&d.name, &detail, config.theme.label_overflow).with_title(title);
                 let doc = create_bar_chart(
-                    text.as_str(),
+                    label,
                     d.size as f64 / self.language_summary.total_size as f64 * 100.0,
                     d.color.as_str(),
                 );
@@ -199,39 +1186,54 @@ impl Renderer {
             chart = chart.add(bar)
         }
 
-        let title = create_chart_title("Top Languages", 0, 30);
+        let title = create_chart_title(languages_title(config), 0, 30);
         root = root.set("x", x).set("y", y).add(title).add(chart);
         root
     }
 
     fn create_top_commits_chart(&self, x: i32, y: i32) -> Document {
-        let config = config::load();
+        let config = &self.config;
+        let locale = config.locale;
         let mut root = Document::new();
         let mut chart = Document::new()
             .set("x", 0)
             .set("y", 50)
             .set("class", "chart");
-        let mut values = self.stats.clone();
-        values = values.into_iter().filter(|item| !item.is_private).collect();
-        values.sort_by(|a, b| {
-            (*b).total_commit_count
-                .partial_cmp(&a.total_commit_count)
-                .unwrap()
-        });
-        values.truncate(config.languages_count);
+        let values = self.shown_commit_repositories();
         let bars: Vec<_> = values
             .into_iter()
             .enumerate()
             .map(|(i, r)| {
-                let text = format!(
-                    "{}: {:.1}% ({})",
-                    r.name,
-                    r.total_commit_count as f64 / self.repository_summary.total_commit_count as f64
-                        * 100.0,
-                    r.total_commit_count
+                let detail = format!(
+                    "{}% ({})",
+                    locale.format_decimal(
+                        r.total_commit_count as f64
+                            / self.repository_summary.total_commit_count as f64
+                            * 100.0,
+                        1
+                    ),
+                    locale.format_number(r.total_commit_count)
+                );
+                let title = i18n::fill(
+                    locale.messages().commits_tooltip,
+                    &[
+                        ("name", &r.name),
+                        (
+                            "share",
+                            &locale.format_decimal(
+                                r.total_commit_count as f64
+                                    / self.repository_summary.total_commit_count as f64
+                                    * 100.0,
+                                2,
+                            ),
+                        ),
+                        ("commits", &locale.format_number(r.total_commit_count)),
+                    ],
                 );
+                let label =
+                    BarLabel::fit(&r.name, &detail, config.theme.label_overflow).with_title(title);
                 let doc = create_bar_chart(
-                    text.as_str(),
+                    label,
                     r.total_commit_count as f64 / self.repository_summary.total_commit_count as f64
                         * 100.0,
                     "url(#blue-grad)",
@@ -244,43 +1246,54 @@ impl Renderer {
             chart = chart.add(bar)
         }
 
-        let title = create_chart_title("Top Commits", 0, 30);
+        let title = create_chart_title(locale.messages().top_commits, 0, 30);
         root = root.set("x", x).set("y", y).add(title).add(chart);
         root
     }
 
     fn create_top_active_commits_chart(&self, x: i32, y: i32) -> Document {
-        let config = config::load();
+        let config = &self.config;
+        let locale = config.locale;
         let mut root = Document::new();
         let mut chart = Document::new()
             .set("x", 0)
             .set("y", 50)
             .set("class", "chart");
-        let mut values = self.stats.clone();
-        values = values
-            .into_iter()
-            .filter(|item| item.period_commit_count > 0 && !item.is_private)
-            .collect();
-        values.sort_by(|a, b| {
-            (*b).period_commit_count
-                .partial_cmp(&a.period_commit_count)
-                .unwrap()
-        });
-        values.truncate(config.languages_count);
+        let values = self.shown_active_commit_repositories();
         let bars: Vec<_> = values
             .into_iter()
             .enumerate()
             .map(|(i, r)| {
-                let text = format!(
-                    "{}: {:.1}% ({})",
-                    r.name,
-                    r.period_commit_count as f64
-                        / self.repository_summary.total_active_commit_count as f64
-                        * 100.0,
-                    r.period_commit_count
+                let detail = format!(
+                    "{}% ({})",
+                    locale.format_decimal(
+                        r.period_commit_count as f64
+                            / self.repository_summary.total_active_commit_count as f64
+                            * 100.0,
+                        1
+                    ),
+                    locale.format_number(r.period_commit_count)
+                );
+                let title = i18n::fill(
+                    locale.messages().commits_tooltip,
+                    &[
+                        ("name", &r.name),
+                        (
+                            "share",
+                            &locale.format_decimal(
+                                r.period_commit_count as f64
+                                    / self.repository_summary.total_active_commit_count as f64
+                                    * 100.0,
+                                2,
+                            ),
+                        ),
+                        ("commits", &locale.format_number(r.period_commit_count)),
+                    ],
                 );
+                let label =
+                    BarLabel::fit(&r.name, &detail, config.theme.label_overflow).with_title(title);
                 let doc = create_bar_chart(
-                    text.as_str(),
+                    label,
                     r.period_commit_count as f64
                         / self.repository_summary.total_active_commit_count as f64
                         * 100.0,
@@ -294,21 +1307,423 @@ impl Renderer {
             chart = chart.add(bar)
         }
 
-        let title = create_chart_title("Top Active Commits(1week)", 0, 30);
+        let title = locale.fill_period(locale.messages().top_active_commits, self.period_days());
+        let title = create_chart_title(&title, 0, 30);
         root = root.set("x", x).set("y", y).add(title).add(chart);
         root
     }
+
+    fn create_top_topics_chart(&self, x: i32, y: i32) -> Document {
+        let config = &self.config;
+        let locale = config.locale;
+        let mut chart = Document::new()
+            .set("x", 0)
+            .set("y", 50)
+            .set("class", "chart");
+        let values = self.top_topics(config.limit(config.limits.topics));
+        // topics overlap, so the bars are relative to the largest one instead of a total
+        let max = values.first().map_or(0, |t| t.commit_count).max(1);
+        for (i, t) in values.iter().enumerate() {
+            let label = BarLabel::fit(
+                &t.name,
+                &topic_detail(t, locale),
+                config.theme.label_overflow,
+            );
+            let bar = create_bar_chart(
+                label,
+                t.commit_count as f64 / max as f64 * 100.0,
+                "url(#blue-grad)",
+            );
+            chart = chart.add(bar.set("y", i * 40));
+        }
+
+        let title = create_chart_title(locale.messages().top_topics, 0, 30);
+        Document::new()
+            .set("x", x)
+            .set("y", y)
+            .add(title)
+            .add(chart)
+    }
+}
+
+///
+/// The part of `total` that the shown bars leave out, when the "Other" bar is enabled.
+///
+fn other_size(config: &Config, total: i64, shown: i64) -> Option<i64> {
+    let rest = total - shown;
+    (config.limits.other && rest > 0).then_some(rest)
+}
+
+fn topic_detail(topic: &TopicSummary, locale: Locale) -> String {
+    i18n::fill(
+        locale.messages().topic_detail,
+        &[
+            (
+                "repositories",
+                &locale.format_number(topic.repository_count),
+            ),
+            ("commits", &locale.format_number(topic.commit_count)),
+        ],
+    )
 }
 
-fn create_bar_chart(label: &str, value: f64, color: &str) -> Document {
+///
+/// Redraw a rendered card in `scheme`. The light colors are the default, so only the dark
+/// scheme adds a style, which wins over the first one as it comes later.
+///
+pub fn apply_color_scheme(document: Document, scheme: ColorScheme) -> Document {
+    match scheme {
+        ColorScheme::Light => document,
+        ColorScheme::Dark => document.add(Style::new(create_dark_css())),
+    }
+}
+
+///
+/// Render the "what's new" panel listing the changes between two snapshots.
+///
+pub fn render_diff(report: &DiffReport, config: &Config) -> Document {
+    const MAX_LINES: usize = 12;
+    let messages = config.locale.messages();
+    let lines = report.lines(config.locale);
+    let description = lines.join(messages.list_separator);
+
+    let mut list = Document::new()
+        .set("x", 0)
+        .set("y", 40)
+        .set("class", "chart");
+    for (i, line) in lines.iter().take(MAX_LINES).enumerate() {
+        list = list.add(
+            Text::new()
+                .set("x", 0)
+                .set("y", 12 + i * 16)
+                .add(svg::node::Text::new(fit_text(line, CHART_WIDTH as f64))),
+        );
+    }
+    let shown = lines.len().min(MAX_LINES) as i32;
+    let chart = Document::new()
+        .set("x", 20)
+        .set("y", 0)
+        .add(create_chart_title(messages.diff_title, 0, 30))
+        .add(list);
+
+    create_card(
+        "diff",
+        CHART_WIDTH + 40,
+        50 + shown * 16,
+        messages.diff_title,
+        &description,
+    )
+    .add(chart)
+}
+
+///
+/// Render the lines changed by language in the commits of the period.
+///
+pub fn render_changes(summary: &LanguageSummary, config: &Config) -> Document {
+    let locale = config.locale;
+    let messages = locale.messages();
+    let mut values: Vec<_> = summary
+        .top(config.limit(config.limits.languages))
+        .into_iter()
+        .cloned()
+        .collect();
+    let shown = values.iter().map(|d| d.size).sum();
+    if let Some(size) = other_size(config, summary.total_size, shown) {
+        values.push(LanguageSummaryValue {
+            name: messages.other.to_string(),
+            color: OTHER_COLOR.to_string(),
+            size,
+        });
+    }
+    let percentage = |size: i64, precision| {
+        locale.format_decimal(size as f64 / summary.total_size as f64 * 100.0, precision)
+    };
+    let lines = |size: i64| i18n::fill(messages.lines, &[("count", &locale.format_number(size))]);
+
+    let languages = values
+        .iter()
+        .map(|d| format!("{} {}", d.name, lines(d.size)))
+        .collect::<Vec<_>>();
+    let description = i18n::fill(
+        messages.lines_changed_description,
+        &[(
+            "languages",
+            &if languages.is_empty() {
+                "-".to_string()
+            } else {
+                languages.join(messages.list_separator)
+            },
+        )],
+    );
+
+    let mut chart = Document::new()
+        .set("x", 0)
+        .set("y", 50)
+        .set("class", "chart");
+    for (i, d) in values.iter().enumerate() {
+        let detail = format!("{}% ({})", percentage(d.size, 1), lines(d.size));
+        let title = format!("{}: {}% ({})", d.name, percentage(d.size, 2), lines(d.size));
+        let label = BarLabel::fit(&d.name, &detail, config.theme.label_overflow).with_title(title);
+        let bar = create_bar_chart(
+            label,
+            d.size as f64 / summary.total_size as f64 * 100.0,
+            d.color.as_str(),
+        );
+        chart = chart.add(bar.set("y", i * 40));
+    }
+    let root = Document::new()
+        .set("x", 20)
+        .set("y", 0)
+        .add(create_chart_title(messages.lines_changed, 0, 30))
+        .add(chart);
+
+    create_card(
+        "changes",
+        CHART_WIDTH + 40,
+        60 + 40 * values.len().max(1) as i32,
+        messages.lines_changed,
+        &description,
+    )
+    .add(root)
+}
+
+///
+/// Drop characters from the end of `text` until `text…suffix` fits into `max_width`.
+///
+fn truncate(text: &str, suffix: &str, font_size: f64, max_width: f64) -> String {
+    let mut chars: Vec<char> = text.chars().collect();
+    loop {
+        chars.pop();
+        let truncated = format!("{}{}{}", chars.iter().collect::<String>(), ELLIPSIS, suffix);
+        if chars.is_empty() || font::measure_text(&truncated, font_size) <= max_width {
+            return truncated;
+        }
+    }
+}
+
+///
+/// Cut `text` with an ellipsis so that it fits into `max_width` at the label font size.
+///
+fn fit_text(text: &str, max_width: f64) -> String {
+    if font::measure_text(text, LABEL_FONT_SIZE) <= max_width {
+        return text.to_string();
+    }
+    truncate(text, "", LABEL_FONT_SIZE, max_width)
+}
+
+///
+/// Aggregate the statistics of all repositories that are not ignored by the config.
+///
+fn summarize(
+    stats: Vec<RepositoryStat>,
+    language_colors: &HashMap<String, String>,
+    language_groups: &HashMap<String, String>,
+    config: &Config,
+    now: DateTime<Utc>,
+) -> (Vec<RepositoryStat>, LanguageSummary, RepositorySummary) {
+    let mut language_summary = LanguageSummary::new();
+    let mut repository_summary = RepositorySummary::new();
+    let private = &config.private_repositories;
+    // every total is taken over the repositories that its panel shows
+    let shown = |s: &RepositoryStat, policy: PrivatePolicy| {
+        !s.is_private || policy != PrivatePolicy::Exclude
+    };
+    // the stars of `ignore_repositories` are still earned, only the filter leaves them out
+    repository_summary.star_count = stats
+        .iter()
+        .filter(|s| config.filter.matches(s, now) && shown(s, private.header))
+        .map(|s| s.stargazer_count)
+        .sum();
+    let stats: Vec<_> = stats
+        .into_iter()
+        .filter(|s| !config.ignore_repositories.contains(&s.name) && config.filter.matches(s, now))
+        .collect();
+    let private_name = config.locale.messages().private_repositories;
+    for s in &stats {
+        debug!("{}, {}", s.name, s.period_commit_count);
+        let weight = language_weight(s, config, now);
+        if shown(s, private.commits) {
+            repository_summary.total_commit_count += s.total_commit_count;
+        }
+        if shown(s, private.active_commits) {
+            repository_summary.total_active_commit_count += s.period_commit_count;
+        }
+        if !shown(s, private.languages) {
+            continue;
+        }
+        let aggregate = s.is_private && private.languages == PrivatePolicy::Aggregate;
+        let ls = &s.languages;
+        for l in ls {
+            // a repository without recent commits does not add its languages at all
+            if weight == 0.0 {
+                continue;
+            }
+            let size = (l.size as f64 * weight).round() as i64;
+            if !aggregate {
+                language_summary.add(
+                    &l.name,
+                    &l.color,
+                    size,
+                    language_colors,
+                    language_groups,
+                    config,
+                );
+            } else if !config.ignore_languages.contains(&l.name) {
+                language_summary.add(
+                    private_name,
+                    PRIVATE_COLOR,
+                    size,
+                    language_colors,
+                    language_groups,
+                    config,
+                );
+            }
+        }
+    }
+    (stats, language_summary, repository_summary)
+}
+
+///
+/// The factor applied to the language sizes of a repository, see `LanguageWeight`.
+///
+fn language_weight(stat: &RepositoryStat, config: &Config, now: DateTime<Utc>) -> f64 {
+    match config.language_weight {
+        LanguageWeight::Size => 1.0,
+        LanguageWeight::Commits => stat.period_commit_count as f64,
+        LanguageWeight::Recency => {
+            let days = (now - stat.pushed_at).num_seconds().max(0) as f64 / 86400.0;
+            0.5_f64.powf(days / config.language_half_life_days)
+        }
+    }
+}
+
+pub fn languages_title(config: &Config) -> &'static str {
+    let messages = config.locale.messages();
+    match config.language_weight {
+        LanguageWeight::Size => messages.top_languages,
+        _ => messages.top_recent_languages,
+    }
+}
+
+///
+/// Draw series as lines over time. All series share one value axis, which starts at zero
+/// when `from_zero` is set and at the smallest value otherwise.
+///
+fn create_line_chart(series: &[Series], from_zero: bool, width: i32, height: i32) -> Document {
+    let times = series.iter().flat_map(|s| s.points.iter().map(|p| p.0));
+    let (start, end) = match (times.clone().min(), times.max()) {
+        (Some(start), Some(end)) => (start, end),
+        // nothing is drawn, so any time will do
+        _ => Default::default(),
+    };
+    let values = series.iter().flat_map(|s| s.points.iter().map(|p| p.1));
+    let max = values.clone().fold(f64::MIN, f64::max);
+    let min = if from_zero {
+        0.0
+    } else {
+        values.fold(f64::MAX, f64::min)
+    };
+    let span = (end - start).num_seconds() as f64;
+    let x = |t: DateTime<Utc>| {
+        if span > 0.0 {
+            (t - start).num_seconds() as f64 / span * width as f64
+        } else {
+            width as f64
+        }
+    };
+    // keep 2px at the top and bottom so that lines on the edge are not clipped
+    let y = |v: f64| {
+        if max > min {
+            2.0 + (height - 4) as f64 * (1.0 - (v - min) / (max - min))
+        } else {
+            height as f64 / 2.0
+        }
+    };
+
+    let baseline = Line::new()
+        .set("x1", 0)
+        .set("y1", height)
+        .set("x2", width)
+        .set("y2", height)
+        .set("stroke", BAR_TRACK_COLOR);
+    let mut root = Document::new()
+        .set("width", width)
+        .set("height", height + 1)
+        .set("class", "trend")
+        .add(baseline);
+    for s in series {
+        if let [(t, v)] = s.points.as_slice() {
+            root = root.add(
+                Circle::new()
+                    .set("cx", x(*t))
+                    .set("cy", y(*v))
+                    .set("r", 2)
+                    .set("fill", s.color.as_str()),
+            );
+        } else if !s.points.is_empty() {
+            let points = s
+                .points
+                .iter()
+                .map(|(t, v)| format!("{:.1},{:.1}", x(*t), y(*v)))
+                .collect::<Vec<_>>()
+                .join(" ");
+            root = root.add(
+                Polyline::new()
+                    .set("points", points)
+                    .set("fill", "none")
+                    .set("stroke", s.color.as_str())
+                    .set("stroke-width", 2)
+                    .set("stroke-linejoin", "round"),
+            );
+        }
+    }
+    root
+}
+
+///
+/// Create the root of a standalone image: size, accessible name and the shared style.
+///
+fn create_card(id: &str, width: i32, height: i32, title: &str, description: &str) -> Document {
+    let title_id = format!("{}-title", id);
+    let desc_id = format!("{}-desc", id);
+    let title = Title::new()
+        .set("id", title_id.as_str())
+        .add(svg::node::Text::new(title));
+    let description = Description::new()
+        .set("id", desc_id.as_str())
+        .add(svg::node::Text::new(description));
+    let defs = Definitions::new()
+        .add(create_gradient("green-grad", "#66ff66", "#009900"))
+        .add(create_gradient("blue-grad", "#66ccff", "#0000ff"));
+
+    Document::new()
+        .set("width", width)
+        .set("height", height)
+        .set("viewBox", format!("0 0 {} {}", width, height))
+        .set("role", "img")
+        .set("aria-labelledby", format!("{} {}", title_id, desc_id))
+        .add(title)
+        .add(description)
+        .add(Style::new(create_css()))
+        .add(defs)
+}
+
+fn create_bar_chart<L: Into<BarLabel>>(label: L, value: f64, color: &str) -> Document {
     const BAR_TOP: f32 = 27.5;
     const BAR_HEIGHT: i32 = 8;
     const BAR_ROUND: i32 = 5;
+    let label = label.into();
     let mut root = Document::new().set("width", CHART_WIDTH);
-    let text = Text::new()
+    if let Some(title) = label.title {
+        root = root.add(Title::new().add(svg::node::Text::new(title)));
+    }
+    let mut text = Text::new()
         .set("x", 0)
         .set("y", 20)
-        .add(svg::node::Text::new(format!("{}", label)));
+        .add(svg::node::Text::new(label.text));
+    if let Some(font_size) = label.font_size {
+        text = text.set("font-size", format!("{:.2}px", font_size));
+    }
     let whole_rect = Rectangle::new()
         .set("x", 0)
         .set("y", BAR_TOP)
@@ -316,7 +1731,7 @@ fn create_bar_chart(label: &str, value: f64, color: &str) -> Document {
         .set("ry", BAR_ROUND)
         .set("width", CHART_WIDTH)
         .set("height", BAR_HEIGHT)
-        .set("fill", "#ddd")
+        .set("fill", BAR_TRACK_COLOR)
         .set("class", "whole");
     let ratio_rect = Rectangle::new()
         .set("x", 0)
@@ -333,20 +1748,21 @@ fn create_bar_chart(label: &str, value: f64, color: &str) -> Document {
     root
 }
 
-fn create_header_pane(count: i64, x: i32, y: i32) -> Document {
+fn create_header_pane(count: i64, locale: Locale, x: i32, y: i32) -> Document {
     let mut root = Document::new().set("class", "star").set("x", x).set("y", y);
 
     let text = Text::new()
         .set("x", 25)
         .set("y", 13)
         .set("width", 100)
-        .add(svg::node::Text::new(format!(
-            "Total Stars Earned: {}",
-            count
+        .add(svg::node::Text::new(i18n::fill(
+            locale.messages().total_stars,
+            &[("count", &locale.format_number(count))],
         )));
 
     let path = Path::new().set("d", STAR_ICON);
     let star = Document::new()
+        .set("aria-hidden", "true")
         .set("viewBox", "0 0 16 16")
         .set("width", 16)
         .set("height", 16)
@@ -356,47 +1772,138 @@ fn create_header_pane(count: i64, x: i32, y: i32) -> Document {
     root
 }
 
-fn create_footer_pane(x: i32, y: i32) -> Document {
-    let now = Utc::now();
-    let current_date = now.format("%Y-%m-%d").to_string();
+fn create_footer_pane(locale: Locale, now: DateTime<Utc>, x: i32, y: i32) -> Document {
+    let messages = locale.messages();
+    let current_date = locale.format_date(&now);
 
-    let text_before = Text::new()
-        .set("x", 0)
-        .set("y", 20)
-        .add(svg::node::Text::new("Generated by"));
+    // The parts flow in one text element, so no width has to be guessed for them.
+    let mut text = Text::new().set("x", 0).set("y", 20);
+    if !messages.generated_before.is_empty() {
+        text = text.add(TSpan::new().add(svg::node::Text::new(messages.generated_before)));
+    }
 
-    let link_text = Text::new()
-        .set("x", 80)
-        .set("y", 20)
-        .add(svg::node::Text::new("github-stats-generator"));
+    let link_text = TSpan::new().add(svg::node::Text::new("github-stats-generator"));
 
     let link = Link::new()
         .set("href", "https://github.com/kengo-k/github-stats-generator")
         .add(link_text);
 
-    let text_after = Text::new()
-        .set("x", 210)
-        .set("y", 20)
-        .add(svg::node::Text::new(format!("at {}", current_date)));
+    let text_after = TSpan::new().add(svg::node::Text::new(i18n::fill(
+        messages.generated_after,
+        &[("date", &current_date)],
+    )));
 
-    let root = Document::new()
+    Document::new()
         .set("class", "footer")
         .set("x", x)
         .set("y", y)
-        .add(text_before)
-        .add(link)
-        .add(text_after);
+        .add(text.add(link).add(text_after))
+}
 
-    root
+///
+/// Warn about theme colors that do not meet the WCAG contrast requirements.
+///
+fn check_theme_contrast() {
+    for (color, background, min) in THEME_CONTRAST_PAIRS {
+        if let Some(ratio) = color::contrast_ratio(color, background) {
+            if ratio < min {
+                warn!(
+                    "low contrast: theme color {} has a ratio of {:.2} against the background {} (< {})",
+                    color, ratio, background, min
+                );
+            }
+        }
+    }
+}
+
+///
+/// Warn about language colors that are hard to distinguish from the bar track.
+///
+fn check_language_contrast(values: &[&LanguageSummaryValue]) {
+    for d in values {
+        match color::contrast_ratio(&d.color, BAR_TRACK_COLOR) {
+            Some(ratio) if ratio < MIN_GRAPHIC_CONTRAST => warn!(
+                "low contrast: color {} of {} has a ratio of {:.2} against the bar track (< {})",
+                d.color, d.name, ratio, MIN_GRAPHIC_CONTRAST
+            ),
+            Some(_) => {}
+            None if d.color.starts_with('#') => {
+                warn!("unknown color format: {} of {}", d.color, d.name)
+            }
+            // CSS color names such as the "red" of languages without a color are not checked
+            None => {}
+        }
+    }
+}
+
+fn create_css() -> String {
+    format!(
+        r#".chart text {{
+    font: 400 9px 'Segoe UI', Ubuntu, {cjk}, Sans-Serif;
+    fill: {text};
+}}
+.title {{
+    font: 600 11px 'Segoe UI', Ubuntu, {cjk}, Sans-Serif;
+    fill: {title};
+}}
+.star path {{
+    fill: {star};
+}}
+.star text {{
+    font: 600 14px 'Segoe UI', Ubuntu, "Helvetica Neue", {cjk}, Sans-Serif;
+    fill: {star_text};
+}}
+.footer > text {{
+    font: 200 11px 'Segoe UI', Ubuntu, {cjk}, Sans-Serif;
+    fill: {footer};
+}}
+.footer > text > a > tspan {{
+    text-decoration: underline;
+}}"#,
+        cjk = CJK_FONTS,
+        text = TEXT_COLOR,
+        title = TITLE_COLOR,
+        star = STAR_COLOR,
+        star_text = STAR_TEXT_COLOR,
+        footer = FOOTER_COLOR,
+    )
+}
+
+fn create_dark_css() -> String {
+    format!(
+        r#":root {{
+    background-color: {background};
+}}
+.chart text {{
+    fill: {text};
+}}
+.title {{
+    fill: {title};
+}}
+.star text {{
+    fill: {star_text};
+}}
+.footer > text {{
+    fill: {footer};
+}}
+.whole {{
+    fill: {track};
+}}"#,
+        background = DARK_BACKGROUND_COLOR,
+        text = DARK_TEXT_COLOR,
+        title = DARK_TITLE_COLOR,
+        star_text = DARK_STAR_TEXT_COLOR,
+        footer = DARK_FOOTER_COLOR,
+        track = DARK_BAR_TRACK_COLOR,
+    )
 }
 
 fn create_chart_title(title: &str, x: i32, y: i32) -> Text {
-    let title = Text::new()
+    Text::new()
         .set("x", x)
         .set("y", y)
         .set("class", "title")
-        .add(svg::node::Text::new(title));
-    title
+        .add(svg::node::Text::new(title))
 }
 
 fn create_gradient(id: &str, from: &str, to: &str) -> LinearGradient {
@@ -406,20 +1913,22 @@ fn create_gradient(id: &str, from: &str, to: &str) -> LinearGradient {
     let stop_to = Stop::new()
         .set("offset", "100%")
         .set("style", format!("stop-color: {}", to));
-    let root = LinearGradient::new()
+    LinearGradient::new()
         .set("id", id)
         .set("x1", "0%")
         .set("y1", "0%")
         .set("x2", "100%")
         .set("y2", "100%")
         .add(stop_from)
-        .add(stop_to);
-    root
+        .add(stop_to)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config;
+    use crate::test_support::{self, create_config, create_language, create_now, create_renderer};
+    use chrono::Duration;
     use sxd_document::{parser, Package};
     use sxd_xpath::{Context, Factory};
 
@@ -531,4 +2040,556 @@ mod tests {
             assert_eq!(actual.trim(), expected);
         }
     }
+
+    #[test]
+    fn test_fit_bar_label() {
+        let label = BarLabel::fit("rust", "9.4% (12)", LabelOverflow::Truncate);
+        assert_eq!(label, BarLabel::from("rust: 9.4% (12)"));
+
+        let name = "a-repository-with-a-really-long-name-that-overflows";
+        let label = BarLabel::fit(name, "9.4% (12)", LabelOverflow::Truncate);
+        assert!(label.text.ends_with("…: 9.4% (12)"));
+        assert!(font::measure_text(&label.text, LABEL_FONT_SIZE) <= CHART_WIDTH as f64);
+        assert_eq!(label.title.unwrap(), format!("{}: 9.4% (12)", name));
+        assert_eq!(label.font_size, None);
+
+        let label = BarLabel::fit(name, "9.4% (12)", LabelOverflow::Shrink);
+        assert_eq!(label.text, format!("{}: 9.4% (12)", name));
+        assert_eq!(label.title, None);
+        let font_size = label.font_size.unwrap();
+        assert!(font_size < LABEL_FONT_SIZE);
+        assert!(font::measure_text(&label.text, font_size) <= CHART_WIDTH as f64);
+
+        let source = create_bar_chart(
+            BarLabel::fit(name, "9.4% (12)", LabelOverflow::Truncate),
+            9.4,
+            "red",
+        )
+        .to_string();
+        let doc =
+            DocumentWrapper::new(source.as_str()).set_namespace("ns", "http://www.w3.org/2000/svg");
+        assert_eq!(
+            doc.string("/ns:svg/ns:title").trim(),
+            format!("{}: 9.4% (12)", name)
+        );
+    }
+
+    #[test]
+    fn test_theme_contrast() {
+        use crate::color::contrast_ratio;
+
+        for (color, background, min) in THEME_CONTRAST_PAIRS {
+            let ratio = contrast_ratio(color, background).unwrap();
+            assert!(
+                ratio >= min,
+                "{} has contrast {:.2} < {} against {}",
+                color,
+                ratio,
+                min,
+                background
+            );
+        }
+        let backgrounds: Vec<_> = THEME_CONTRAST_PAIRS.iter().map(|p| p.1).collect();
+        assert!(backgrounds.contains(&BACKGROUND_COLOR));
+        assert!(backgrounds.contains(&DARK_BACKGROUND_COLOR));
+    }
+
+    fn create_stat(
+        name: &str,
+        total_commit_count: i64,
+        period_commit_count: i64,
+    ) -> RepositoryStat {
+        RepositoryStat {
+            disk_usage: 100,
+            stargazer_count: 1,
+            languages: vec![create_language("Rust", "#dea584", 1000)],
+            total_commit_count,
+            period_commit_count,
+            ..test_support::create_stat(name)
+        }
+    }
+
+    #[test]
+    fn test_language_weight() {
+        let config = |weight: &str| -> config::Config {
+            toml::from_str(&format!(
+                r#"
+                languages_count = 10
+                language_weight = "{}"
+                ignore_repositories = []
+                ignore_languages = []
+                [language_mapping]
+                [rename_language]
+                "#,
+                weight
+            ))
+            .unwrap()
+        };
+        let mut old = create_stat("old", 100, 0);
+        old.languages[0].name = "Go".to_string();
+        old.languages[0].size = 100_000;
+        let now = create_now();
+        old.pushed_at = now - chrono::Duration::days(60);
+        let mut new = create_stat("new", 10, 3);
+        new.pushed_at = now;
+        let stats = vec![old, new];
+        let size = |summary: &LanguageSummary, name: &str| summary.data.get(name).map(|v| v.size);
+
+        let (_, summary, _) = summarize(
+            stats.clone(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &config("size"),
+            now,
+        );
+        assert_eq!(size(&summary, "Go"), Some(100_000));
+        assert_eq!(size(&summary, "Rust"), Some(1000));
+
+        let (_, summary, _) = summarize(
+            stats.clone(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &config("commits"),
+            now,
+        );
+        assert_eq!(size(&summary, "Go"), None);
+        assert_eq!(size(&summary, "Rust"), Some(3000));
+        assert_eq!(summary.total_size, 3000);
+
+        // two half-lives of 30 days
+        let (_, summary, _) = summarize(
+            stats,
+            &HashMap::new(),
+            &HashMap::new(),
+            &config("recency"),
+            now,
+        );
+        assert_eq!(size(&summary, "Go"), Some(25_000));
+        assert_eq!(size(&summary, "Rust"), Some(1000));
+    }
+
+    #[test]
+    fn test_private_policy() {
+        let config: config::Config = toml::from_str(
+            r#"
+            languages_count = 10
+            ignore_repositories = []
+            ignore_languages = []
+            [language_mapping]
+            [rename_language]
+            [private_repositories]
+            header = "exclude"
+            languages = "aggregate"
+            commits = "anonymize"
+            active_commits = "exclude"
+            "#,
+        )
+        .unwrap();
+        let mut secret = create_stat("secret", 20, 4);
+        secret.is_private = true;
+        secret.stargazer_count = 7;
+        let stats = vec![create_stat("foo", 10, 2), secret.clone(), secret];
+
+        let (_, languages, repositories) = summarize(
+            stats.clone(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &config,
+            create_now(),
+        );
+        assert_eq!(repositories.star_count, 1);
+        assert_eq!(repositories.total_commit_count, 50);
+        assert_eq!(repositories.total_active_commit_count, 2);
+        assert_eq!(languages.data["Rust"].size, 1000);
+        assert_eq!(languages.data["Private"].size, 2000);
+        assert_eq!(languages.data["Private"].color, PRIVATE_COLOR);
+
+        let renderer = create_renderer(stats, config);
+        let names = |policy| {
+            renderer
+                .population(policy)
+                .into_iter()
+                .map(|r| (r.name, r.total_commit_count))
+                .collect::<Vec<_>>()
+        };
+        let foo = ("foo".to_string(), 10);
+        assert_eq!(
+            names(PrivatePolicy::Anonymize),
+            vec![
+                foo.clone(),
+                ("private repo #1".to_string(), 20),
+                ("private repo #2".to_string(), 20)
+            ]
+        );
+        assert_eq!(
+            names(PrivatePolicy::Aggregate),
+            vec![foo.clone(), ("Private".to_string(), 40)]
+        );
+        assert_eq!(names(PrivatePolicy::Exclude), vec![foo]);
+        assert_eq!(names(PrivatePolicy::Include).len(), 3);
+    }
+
+    #[test]
+    fn test_ignored_stars() {
+        let config: config::Config = toml::from_str(
+            r#"
+            languages_count = 10
+            ignore_repositories = ["bar"]
+            ignore_languages = []
+            [language_mapping]
+            [rename_language]
+            "#,
+        )
+        .unwrap();
+        let mut fork = create_stat("fork", 1, 1);
+        fork.is_fork = true;
+        fork.stargazer_count = 99;
+        let mut bar = create_stat("bar", 1, 1);
+        bar.stargazer_count = 4;
+        let stats = vec![create_stat("foo", 10, 2), bar, fork];
+        let (stats, _, repositories) = summarize(
+            stats,
+            &HashMap::new(),
+            &HashMap::new(),
+            &config,
+            create_now(),
+        );
+        assert_eq!(stats.len(), 1);
+        // bar is ignored but its stars are earned, the fork is filtered out
+        assert_eq!(repositories.star_count, 5);
+    }
+
+    #[test]
+    fn test_topics() {
+        let mut foo = create_stat("foo", 10, 2);
+        foo.topics = vec!["cli".to_string(), "rust".to_string()];
+        let mut bar = create_stat("bar", 5, 1);
+        bar.topics = vec!["rust".to_string()];
+        let mut baz = create_stat("baz", 30, 0);
+        baz.topics = vec!["web".to_string()];
+        let stats = vec![foo, bar, baz, create_stat("qux", 1, 0)];
+
+        let renderer = create_renderer(stats.clone(), create_config());
+        let topics = renderer.top_topics(2);
+        assert_eq!(
+            topics
+                .iter()
+                .map(|t| (t.name.as_str(), t.repository_count, t.commit_count))
+                .collect::<Vec<_>>(),
+            vec![("web", 1, 30), ("rust", 2, 15)]
+        );
+
+        let config: config::Config = toml::from_str(
+            r#"
+            languages_count = 10
+            ignore_repositories = []
+            ignore_languages = []
+            [language_mapping]
+            [rename_language]
+            [projects]
+            Toolbox = ["rust"]
+            Zzz = ["cli", "web"]
+            "#,
+        )
+        .unwrap();
+        let renderer = create_renderer(stats, config);
+        let grouped = renderer.population(PrivatePolicy::Include);
+        assert_eq!(
+            grouped
+                .iter()
+                .map(|r| (r.name.as_str(), r.total_commit_count, r.period_commit_count))
+                .collect::<Vec<_>>(),
+            vec![("Toolbox", 15, 3), ("Zzz", 30, 0), ("qux", 1, 0)]
+        );
+        assert_eq!(grouped[0].kind, RowKind::Project);
+    }
+
+    #[test]
+    fn test_other_bucket() {
+        let config = |other: bool| -> config::Config {
+            toml::from_str(&format!(
+                r#"
+                languages_count = 10
+                ignore_repositories = []
+                ignore_languages = []
+                [language_mapping]
+                [rename_language]
+                [limits]
+                languages = 1
+                commits = 2
+                other = {}
+                "#,
+                other
+            ))
+            .unwrap()
+        };
+        let mut go = create_stat("go", 5, 0);
+        go.languages[0].name = "Go".to_string();
+        go.languages[0].size = 500;
+        let stats = vec![create_stat("foo", 10, 2), go, create_stat("bar", 1, 1)];
+        let renderer = |other| create_renderer(stats.clone(), config(other));
+
+        let languages = renderer(true).shown_languages();
+        assert_eq!(
+            languages
+                .iter()
+                .map(|d| (d.name.as_str(), d.size, d.color.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("Rust", 2000, "#dea584"), ("Other", 500, OTHER_COLOR)]
+        );
+        assert_eq!(renderer(false).shown_languages().len(), 1);
+
+        let commits = renderer(true).shown_commit_repositories();
+        assert_eq!(
+            commits
+                .iter()
+                .map(|r| (r.name.as_str(), r.total_commit_count))
+                .collect::<Vec<_>>(),
+            vec![("foo", 10), ("go", 5), ("Other", 1)]
+        );
+        // nothing is left out, so there is no "Other"
+        assert_eq!(renderer(true).shown_active_commit_repositories().len(), 2);
+    }
+
+    #[test]
+    fn test_language_groups() {
+        let config: config::Config = toml::from_str(
+            r##"
+            languages_count = 10
+            ignore_repositories = []
+            ignore_languages = []
+            linguist_groups = true
+            [language_mapping]
+            SCSS = "CSS"
+            [rename_language]
+            Web = "Web Stack"
+            [[language_groups]]
+            name = "Web"
+            languages = ["*Script", "CSS"]
+            color = "#123456"
+            [[language_groups]]
+            name = "Shell"
+            languages = ["/^(Shell|PowerShell|Batchfile)$/"]
+            "##,
+        )
+        .unwrap();
+        let stat = |languages: &[&str]| RepositoryStat {
+            languages: languages
+                .iter()
+                .map(|l| create_language(l, "#000000", 100))
+                .collect(),
+            ..create_stat("foo", 1, 0)
+        };
+        let stats = vec![
+            stat(&["TypeScript", "JavaScript", "SCSS"]),
+            stat(&["PowerShell", "Batchfile", "TSX", "Rust"]),
+        ];
+        let colors = HashMap::from([
+            ("Shell".to_string(), "#89e051".to_string()),
+            ("TypeScript".to_string(), "#3178c6".to_string()),
+        ]);
+        let groups = HashMap::from([("TSX".to_string(), "TypeScript".to_string())]);
+
+        let (_, summary, _) = summarize(stats, &colors, &groups, &config, create_now());
+        let value = |name: &str| summary.data.get(name).map(|v| (v.size, v.color.as_str()));
+        assert_eq!(value("Web Stack"), Some((300, "#123456")));
+        assert_eq!(value("Shell"), Some((200, "#89e051")));
+        // TSX does not end with "Script", so it goes to its linguist group
+        assert_eq!(value("TypeScript"), Some((100, "#3178c6")));
+        assert_eq!(value("Rust"), Some((100, "#000000")));
+        assert_eq!(summary.total_size, 700);
+    }
+
+    #[test]
+    fn test_render_panel() {
+        let stats = vec![create_stat("foo", 10, 2), create_stat("bar", 5, 0)];
+        let mut renderer = create_renderer(stats, create_config());
+
+        let source = renderer.render_panel(Panel::Commits).to_string();
+        let doc =
+            DocumentWrapper::new(source.as_str()).set_namespace("ns", "http://www.w3.org/2000/svg");
+        assert_eq!(doc.string("/ns:svg/@viewBox"), "0 0 240 140");
+        assert_eq!(
+            doc.string("/ns:svg/@aria-labelledby"),
+            "commits-title commits-desc"
+        );
+        assert_eq!(doc.string("/ns:svg/ns:title").trim(), "Top Commits");
+        assert_eq!(
+            doc.string("/ns:svg/ns:desc").trim(),
+            "Top commits: foo 10, bar 5."
+        );
+        assert_eq!(doc.string("count(/ns:svg/ns:svg/ns:svg/ns:svg)"), "2");
+
+        let source = renderer.render_panel(Panel::ActiveCommits).to_string();
+        let doc =
+            DocumentWrapper::new(source.as_str()).set_namespace("ns", "http://www.w3.org/2000/svg");
+        assert_eq!(doc.string("/ns:svg/@viewBox"), "0 0 240 100");
+        assert_eq!(doc.string("count(/ns:svg/ns:svg/ns:svg/ns:svg)"), "1");
+    }
+
+    #[test]
+    fn test_render_star_history() {
+        let mut foo = create_stat("foo", 10, 2);
+        foo.stargazer_count = 3;
+        foo.starred_at = vec![
+            "2023-07-01T00:00:00Z".parse().unwrap(),
+            "2023-08-03T00:00:00Z".parse().unwrap(),
+            "2023-08-03T12:00:00Z".parse().unwrap(),
+        ];
+        let mut bar = create_stat("bar", 5, 0);
+        bar.starred_at = vec!["2023-08-02T00:00:00Z".parse().unwrap()];
+        let renderer = create_renderer(vec![foo, bar, create_stat("baz", 1, 0)], create_config());
+
+        let from = "2023-08-01T00:00:00Z".parse().unwrap();
+        let to = "2023-08-08T00:00:00Z".parse().unwrap();
+        let source = renderer.render_star_history(from, to).to_string();
+        let doc =
+            DocumentWrapper::new(source.as_str()).set_namespace("ns", "http://www.w3.org/2000/svg");
+        assert_eq!(
+            doc.string("/ns:svg/ns:desc").trim(),
+            "4 stars as of 2023-08-08, 3 gained since 2023-08-01: foo +2, bar +1."
+        );
+        assert_eq!(
+            doc.string("count(//ns:text[normalize-space(.) = '4 stars (+3 in the last week)'])"),
+            "1"
+        );
+        // the two stars of 2023-08-03 share one point
+        assert_eq!(
+            doc.string("//ns:polyline/@points"),
+            "0.0,59.0 168.4,40.0 176.3,2.0 200.0,2.0"
+        );
+        assert_eq!(doc.string("/ns:svg/@viewBox"), "0 0 240 265");
+        assert_eq!(
+            doc.string("//ns:svg[@class = 'chart']/ns:svg[1]/ns:text")
+                .trim(),
+            "foo: +2 (3)"
+        );
+
+        let source = renderer.render_star_history(to, to).to_string();
+        let doc =
+            DocumentWrapper::new(source.as_str()).set_namespace("ns", "http://www.w3.org/2000/svg");
+        assert_eq!(doc.string("/ns:svg/@viewBox"), "0 0 240 145");
+    }
+
+    #[test]
+    fn test_render_trend() {
+        let renderer = create_renderer(vec![create_stat("foo", 10, 2)], create_config());
+        let history = vec![
+            Snapshot {
+                timestamp: "2023-08-01T00:00:00Z".parse().unwrap(),
+                stats: vec![create_stat("foo", 10, 2)],
+            },
+            Snapshot {
+                timestamp: "2023-08-08T00:00:00Z".parse().unwrap(),
+                stats: vec![create_stat("foo", 25, 2), create_stat("bar", 1, 1)],
+            },
+        ];
+
+        let source = renderer.render_trend(&history, Trend::Commits).to_string();
+        let doc =
+            DocumentWrapper::new(source.as_str()).set_namespace("ns", "http://www.w3.org/2000/svg");
+        assert_eq!(
+            doc.string("/ns:svg/ns:desc").trim(),
+            "Total Commits from 2023-08-01 to 2023-08-08: 10 → 26."
+        );
+        assert_eq!(
+            doc.string("count(//ns:text[normalize-space(.) = '26 (+16)'])"),
+            "1"
+        );
+        assert_eq!(doc.string("//ns:polyline/@points"), "0.0,78.0 200.0,2.0");
+
+        let source = renderer
+            .render_trend(&history[..1], Trend::Languages)
+            .to_string();
+        let doc =
+            DocumentWrapper::new(source.as_str()).set_namespace("ns", "http://www.w3.org/2000/svg");
+        assert_eq!(doc.string("count(//ns:circle)"), "1");
+        assert_eq!(
+            doc.string("/ns:svg/ns:desc").trim(),
+            "Language Share from 2023-08-01 to 2023-08-01: Rust 100.0% → 100.0%."
+        );
+
+        // a snapshot is counted as of when it was taken, not as of now
+        let config: config::Config = toml::from_str(
+            r#"
+            languages_count = 10
+            ignore_repositories = []
+            ignore_languages = []
+            [language_mapping]
+            [rename_language]
+            [filter]
+            max_push_age_days = 30
+            "#,
+        )
+        .unwrap();
+        let now: DateTime<Utc> = "2024-08-08T00:00:00Z".parse().unwrap();
+        let renderer = Renderer::with_config(
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            config,
+            (now - Duration::days(7), now),
+        );
+        let source = renderer.render_trend(&history, Trend::Commits).to_string();
+        let doc =
+            DocumentWrapper::new(source.as_str()).set_namespace("ns", "http://www.w3.org/2000/svg");
+        assert_eq!(
+            doc.string("/ns:svg/ns:desc").trim(),
+            "Total Commits from 2023-08-01 to 2023-08-08: 10 → 26."
+        );
+    }
+
+    ///
+    /// Compare an image with its file in `testdata/golden` byte for byte. Set `UPDATE_GOLDEN`
+    /// to write the files instead, e.g. after changing the look of the card.
+    ///
+    fn assert_golden(name: &str, document: &Document) {
+        let path = format!("testdata/golden/{}", name);
+        let actual = document.to_string();
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, &actual).unwrap();
+        }
+        let expected = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            actual == expected,
+            "{} differs, run the tests with UPDATE_GOLDEN=1 to update it",
+            path
+        );
+    }
+
+    fn create_fixture_renderer(reverse: bool) -> Renderer {
+        let content = std::fs::read_to_string("testdata/repositories.json").unwrap();
+        let mut stats: Vec<RepositoryStat> = serde_json::from_str(&content).unwrap();
+        if reverse {
+            stats.reverse();
+        }
+        // the golden files must not change with config.toml
+        let config = toml::from_str(
+            r#"
+            languages_count = 10
+            ignore_repositories = []
+            ignore_languages = []
+            [language_mapping]
+            SCSS = "CSS"
+            [rename_language]
+            CSS = "CSS/SCSS"
+            "#,
+        )
+        .unwrap();
+        create_renderer(stats, config)
+    }
+
+    #[test]
+    fn test_golden() {
+        let mut renderer = create_fixture_renderer(false);
+        assert_golden("github_stats.svg", &renderer.render());
+        for panel in Panel::ALL {
+            let name = format!("github_stats_{}.svg", panel.name());
+            assert_golden(&name, &renderer.render_panel(panel));
+        }
+
+        // equal sizes and counts are ordered by name, not by where they come from
+        let mut reversed = create_fixture_renderer(true);
+        assert_eq!(reversed.render().to_string(), renderer.render().to_string());
+    }
 }
@@ -0,0 +1,151 @@
+use chrono::{DateTime, Datelike, Duration, DurationRound, Timelike, Utc};
+use serde::Deserialize;
+
+///
+/// A cron expression of five fields, `minute hour day-of-month month day-of-week`, read in UTC.
+/// Each field is `*`, a number, a range `a-b`, a step `*/n` or `a-b/n`, or a list of them
+/// separated by commas. Day-of-week is 0 to 7 where both 0 and 7 are Sunday.
+///
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Like cron, when both days are restricted a day matching either of them runs.
+    either_day: bool,
+}
+
+impl TryFrom<String> for Cron {
+    type Error = String;
+
+    fn try_from(expression: String) -> Result<Self, Self::Error> {
+        let fields: Vec<_> = expression.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields.as_slice() else {
+            return Err(format!("a cron expression has five fields: {}", expression));
+        };
+        let mut weekdays = parse_field(weekdays, 0, 7)?;
+        if weekdays & 1 << 7 != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_field(minutes, 0, 59)?,
+            hours: parse_field(hours, 0, 23)?,
+            days: parse_field(days, 1, 31)?,
+            months: parse_field(months, 1, 12)?,
+            weekdays,
+            either_day: !days.starts_with('*') && !fields[4].starts_with('*'),
+        })
+    }
+}
+
+///
+/// Parse one field into a bit set of the values it matches.
+///
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let invalid = || format!("invalid cron field: {}", field);
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (
+                start.parse().map_err(|_| invalid())?,
+                end.parse().map_err(|_| invalid())?,
+            ),
+            None => {
+                let value = range.parse().map_err(|_| invalid())?;
+                // `5/15` means from 5 to the end in steps of 15
+                (value, if part.contains('/') { max } else { value })
+            }
+        };
+        if step == 0 || start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+impl Cron {
+    fn matches_day(&self, time: DateTime<Utc>) -> bool {
+        let day = self.days & 1 << time.day() != 0;
+        let weekday = self.weekdays & 1 << time.weekday().num_days_from_sunday() != 0;
+        let day = if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        };
+        day && self.months & 1 << time.month() != 0
+    }
+
+    ///
+    /// The first minute after `time` that the expression matches, or `None` when it matches
+    /// no date within the next five years, e.g. `0 0 31 2 *`.
+    ///
+    pub fn next_after(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = time.duration_trunc(Duration::minutes(1)).ok()? + Duration::minutes(1);
+        let mut next = start;
+        while next - start < Duration::days(5 * 366) {
+            if !self.matches_day(next) {
+                next = next.duration_trunc(Duration::days(1)).ok()? + Duration::days(1);
+            } else if self.hours & 1 << next.hour() == 0 {
+                next = next.duration_trunc(Duration::hours(1)).ok()? + Duration::hours(1);
+            } else if self.minutes & 1 << next.minute() == 0 {
+                next += Duration::minutes(1);
+            } else {
+                return Some(next);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cron(expression: &str) -> Cron {
+        Cron::try_from(expression.to_string()).unwrap()
+    }
+
+    fn time(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_field("*", 0, 5), Ok(0b111111));
+        assert_eq!(parse_field("1,3-4", 0, 5), Ok(0b11010));
+        assert_eq!(parse_field("*/2", 0, 5), Ok(0b10101));
+        assert_eq!(parse_field("1/2", 0, 5), Ok(0b101010));
+        assert!(parse_field("6", 0, 5).is_err());
+        assert!(parse_field("4-2", 0, 5).is_err());
+        assert!(parse_field("*/0", 0, 5).is_err());
+        assert!(Cron::try_from("0 * * *".to_string()).is_err());
+        assert_eq!(cron("0 0 * * 7"), cron("0 0 * * 0"));
+    }
+
+    #[test]
+    fn test_next_after() {
+        let now = time("2023-08-08T10:17:30Z");
+        let next = |expression: &str| cron(expression).next_after(now);
+        assert_eq!(next("* * * * *"), Some(time("2023-08-08T10:18:00Z")));
+        assert_eq!(next("*/15 * * * *"), Some(time("2023-08-08T10:30:00Z")));
+        assert_eq!(next("0 */6 * * *"), Some(time("2023-08-08T12:00:00Z")));
+        assert_eq!(next("0 9 * * *"), Some(time("2023-08-09T09:00:00Z")));
+        // 2023-08-08 is a Tuesday
+        assert_eq!(next("30 8 * * 1"), Some(time("2023-08-14T08:30:00Z")));
+        assert_eq!(next("0 0 1 1 *"), Some(time("2024-01-01T00:00:00Z")));
+        // either the 10th or a Monday
+        assert_eq!(next("0 0 10 * 1"), Some(time("2023-08-10T00:00:00Z")));
+        assert_eq!(next("0 0 31 2 *"), None);
+    }
+}
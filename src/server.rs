@@ -0,0 +1,749 @@
+use crate::config::{Config, Server};
+use crate::envelope::Envelope;
+use crate::generated::github_stats::git_hub_stats::ResponseData;
+use crate::graphql::{self, GitHub, GraphQLResponse, RepositoryStat};
+use crate::metrics;
+use crate::renderer::{self, ColorScheme, Panel, Renderer};
+use crate::{color, AppError};
+use chrono::{DateTime, Duration, Utc};
+use hyper::header::{HeaderValue, CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH, RETRY_AFTER};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use log::{info, warn};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::hash::{Hash, Hasher};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex as SyncMutex};
+use tokio::sync::Mutex;
+
+/// The period of a request without `period`, the same week as the generated cards.
+const DEFAULT_PERIOD_DAYS: i64 = crate::PERIOD_DAYS;
+
+/// The periods a request may ask for, up to `max_period_days`. With the allowed users they
+/// bound the number of snapshots that are kept.
+const PERIODS_DAYS: [i64; 4] = [DEFAULT_PERIOD_DAYS, 30, 90, 365];
+
+/// A snapshot that no request asked for in this long is dropped instead of refreshed.
+const IDLE_HOURS: i64 = 24;
+
+///
+/// Whose statistics over how many days. The login is lowercase, and `None` for the owner of
+/// the token.
+///
+type SnapshotKey = (Option<String>, i64);
+
+/// The start of the current minute of fetches of a user, and how many were made in it.
+type FetchWindow = (DateTime<Utc>, u32);
+
+///
+/// The statistics of one period as they were last fetched
+///
+#[derive(Debug, Clone)]
+struct Snapshot {
+    fetched_at: DateTime<Utc>,
+    login: String,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    stats: Vec<RepositoryStat>,
+}
+
+///
+/// A key of the cache: its snapshot, when a request last asked for it, and the lock held while
+/// it is fetched, so that concurrent requests for it wait for one fetch
+///
+#[derive(Debug, Default)]
+struct Entry {
+    snapshot: Option<Snapshot>,
+    requested_at: Option<DateTime<Utc>>,
+    fetching: Arc<Mutex<()>>,
+}
+
+///
+/// What the requests share: the API, the config and the linguist settings, read once, the
+/// snapshots, and the recent fetches of each user
+///
+#[derive(Debug)]
+struct State {
+    github: GitHub,
+    language_colors: HashMap<String, String>,
+    language_groups: HashMap<String, String>,
+    config: Config,
+    /// Never locked across a fetch.
+    snapshots: SyncMutex<HashMap<SnapshotKey, Entry>>,
+    fetches: SyncMutex<HashMap<Option<String>, FetchWindow>>,
+}
+
+///
+/// The options of a card request, e.g. `?theme=dark&layout=compact&period=30d`
+///
+#[derive(Debug, PartialEq)]
+struct CardQuery {
+    scheme: ColorScheme,
+    compact: bool,
+    period_days: i64,
+}
+
+///
+/// Serve the cards until the process is stopped.
+///
+pub async fn serve(config: Config) -> Result<(), AppError> {
+    let linguist_languages = color::get_linguist_languages()
+        .await
+        .map_err(|_| AppError::ConvertError)?;
+    let state = Arc::new(State {
+        github: GitHub::from_env(),
+        language_colors: color::get_language_color_settings(&linguist_languages),
+        language_groups: color::get_language_group_settings(&linguist_languages),
+        config,
+        snapshots: SyncMutex::new(HashMap::new()),
+        fetches: SyncMutex::new(HashMap::new()),
+    });
+
+    tokio::spawn(refresh(state.clone()));
+
+    let addr = SocketAddr::from(([0, 0, 0, 0], state.config.server.port));
+    let make_service = make_service_fn(move |_| {
+        let state = state.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let state = state.clone();
+                async move { Ok::<_, Infallible>(handle(&state, request).await) }
+            }))
+        }
+    });
+    info!("listening on http://{}", addr);
+    hyper::Server::bind(&addr)
+        .serve(make_service)
+        .await
+        .map_err(|_| AppError::ServerError)
+}
+
+///
+/// Fetch the default card of the token owner and every snapshot that was requested lately
+/// again each time they go stale, so that requests are answered from memory.
+///
+async fn refresh(state: Arc<State>) {
+    let mut interval =
+        tokio::time::interval(refresh_interval(&state.config.server).to_std().unwrap());
+    loop {
+        interval.tick().await;
+        for key in evict(&state, Utc::now()) {
+            if let Err(e) = get_snapshot(&state, &key, None).await {
+                warn!("failed to refresh {:?}: {:?}", key, e);
+            }
+        }
+    }
+}
+
+fn refresh_interval(settings: &Server) -> Duration {
+    Duration::minutes(settings.refresh_minutes.max(1))
+}
+
+///
+/// Drop the snapshots that no request asked for in `IDLE_HOURS`, and return the keys to keep
+/// fresh: the rest and the default card of the token owner.
+///
+fn evict(state: &State, now: DateTime<Utc>) -> Vec<SnapshotKey> {
+    let default_key = (None, DEFAULT_PERIOD_DAYS);
+    let mut snapshots = state.snapshots.lock().unwrap();
+    snapshots.retain(|key, entry| {
+        *key == default_key
+            || entry
+                .requested_at
+                .map_or(false, |t| now - t < Duration::hours(IDLE_HOURS))
+    });
+    let mut keys: Vec<SnapshotKey> = snapshots.keys().cloned().collect();
+    if !keys.contains(&default_key) {
+        keys.push(default_key);
+    }
+    keys
+}
+
+///
+/// The snapshot of an entry while it is younger than `refresh_minutes`.
+///
+fn fresh_snapshot(entry: &Entry, settings: &Server, now: DateTime<Utc>) -> Option<Snapshot> {
+    entry
+        .snapshot
+        .as_ref()
+        .filter(|s| now - s.fetched_at < refresh_interval(settings))
+        .cloned()
+}
+
+///
+/// Get a snapshot, fetching it when it is missing or stale. `requested_at` is the time of the
+/// request that asks for it, `None` for the refresh. When the fetch fails, or a request would
+/// fetch beyond `max_fetches_per_minute`, the stale snapshot is served rather than nothing.
+///
+async fn get_snapshot(
+    state: &State,
+    key: &SnapshotKey,
+    requested_at: Option<DateTime<Utc>>,
+) -> Result<Snapshot, AppError> {
+    let settings = &state.config.server;
+    let fetching = {
+        let mut snapshots = state.snapshots.lock().unwrap();
+        let entry = snapshots.entry(key.clone()).or_default();
+        if requested_at.is_some() {
+            entry.requested_at = requested_at;
+        }
+        if let Some(snapshot) = fresh_snapshot(entry, settings, Utc::now()) {
+            return Ok(snapshot);
+        }
+        entry.fetching.clone()
+    };
+    let _fetching = fetching.lock().await;
+    // a request that held the lock before may have fetched it already
+    let stale = match state.snapshots.lock().unwrap().get(key) {
+        Some(entry) => match fresh_snapshot(entry, settings, Utc::now()) {
+            Some(snapshot) => return Ok(snapshot),
+            None => entry.snapshot.clone(),
+        },
+        None => None,
+    };
+
+    let now = Utc::now();
+    let (login, period_days) = key;
+    let from = now - Duration::days(*period_days);
+    let fetched = if requested_at.is_some() && !accept_fetch(state, login, now) {
+        Err(AppError::TooManyRequestsError)
+    } else {
+        fetch(&state.github, login, from, now, &state.config).await
+    };
+    match fetched {
+        Ok(response) => {
+            let snapshot = Snapshot {
+                fetched_at: now,
+                login: response.data.viewer.login.clone(),
+                from,
+                to: now,
+                stats: graphql::normalize(response.data),
+            };
+            let mut snapshots = state.snapshots.lock().unwrap();
+            snapshots.entry(key.clone()).or_default().snapshot = Some(snapshot.clone());
+            Ok(snapshot)
+        }
+        Err(e) => match stale {
+            Some(snapshot) => {
+                warn!("serving the stale {:?}: {:?}", key, e);
+                Ok(snapshot)
+            }
+            None => Err(e),
+        },
+    }
+}
+
+async fn fetch(
+    github: &GitHub,
+    login: &Option<String>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    config: &Config,
+) -> Result<GraphQLResponse<ResponseData>, AppError> {
+    let (from, to) = (from.to_rfc3339(), to.to_rfc3339());
+    let is_fork = config.filter.fork.flag();
+    let stargazers = config.output.star_history;
+    match login {
+        None => graphql::get_github_stats(github, from, to, is_fork, stargazers).await,
+        Some(login) => {
+            graphql::get_user_stats(github, login.clone(), from, to, is_fork, stargazers)
+                .await
+                .and_then(|response| response.ok_or(AppError::UserNotFoundError))
+        }
+    }
+}
+
+///
+/// Count a fetch for the user, and tell whether it is within `max_fetches_per_minute`.
+///
+fn accept_fetch(state: &State, login: &Option<String>, now: DateTime<Utc>) -> bool {
+    let mut fetches = state.fetches.lock().unwrap();
+    let (start, count) = fetches.entry(login.clone()).or_insert((now, 0));
+    if now - *start >= Duration::minutes(1) {
+        (*start, *count) = (now, 0);
+    }
+    *count += 1;
+    *count <= state.config.server.max_fetches_per_minute
+}
+
+///
+/// Read the query string of a card request. Unknown parameters are ignored.
+///
+fn parse_query(query: Option<&str>, settings: &Server) -> Result<CardQuery, String> {
+    let mut card_query = CardQuery {
+        scheme: ColorScheme::Light,
+        compact: false,
+        period_days: DEFAULT_PERIOD_DAYS,
+    };
+    for pair in query
+        .unwrap_or_default()
+        .split('&')
+        .filter(|p| !p.is_empty())
+    {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        match key {
+            "theme" => {
+                card_query.scheme = match value {
+                    "light" => ColorScheme::Light,
+                    "dark" => ColorScheme::Dark,
+                    _ => return Err(format!("unknown theme: {}", value)),
+                }
+            }
+            "layout" => {
+                card_query.compact = match value {
+                    "normal" => false,
+                    "compact" => true,
+                    _ => return Err(format!("unknown layout: {}", value)),
+                }
+            }
+            "period" => {
+                let periods: Vec<_> = PERIODS_DAYS
+                    .into_iter()
+                    .filter(|days| *days <= settings.max_period_days)
+                    .collect();
+                card_query.period_days = value
+                    .strip_suffix('d')
+                    .and_then(|days| days.parse().ok())
+                    .filter(|days| periods.contains(days))
+                    .ok_or_else(|| {
+                        let periods = periods
+                            .iter()
+                            .map(|days| format!("{}d", days))
+                            .collect::<Vec<_>>();
+                        format!("period must be one of {}: {}", periods.join(", "), value)
+                    })?
+            }
+            _ => (),
+        }
+    }
+    Ok(card_query)
+}
+
+///
+/// The card a path asks for
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Route {
+    Card,
+    Panel(Panel),
+    Stars,
+    Stats,
+    Metrics,
+}
+
+///
+/// Split a path such as `/octocat/card.svg` into the login as written and the card. Paths
+/// without a login are the cards of the owner of the token.
+///
+fn route(path: &str) -> Option<(Option<String>, Route)> {
+    match path.strip_prefix('/')?.split_once('/') {
+        Some((login, card)) => Some((Some(login.to_string()), card_route(card)?)),
+        None => Some((None, card_route(path.strip_prefix('/')?)?)),
+    }
+}
+
+fn card_route(card: &str) -> Option<Route> {
+    match card {
+        "card.svg" => Some(Route::Card),
+        "langs.svg" => Some(Route::Panel(Panel::Languages)),
+        "stars.svg" => Some(Route::Stars),
+        "stats.json" => Some(Route::Stats),
+        "metrics" => Some(Route::Metrics),
+        _ => {
+            let name = card.strip_suffix(".svg")?;
+            let panel = Panel::ALL.into_iter().find(|p| p.name() == name)?;
+            Some(Route::Panel(panel))
+        }
+    }
+}
+
+async fn handle(state: &State, request: Request<Body>) -> Response<Body> {
+    if request.method() != Method::GET && request.method() != Method::HEAD {
+        return error(
+            StatusCode::METHOD_NOT_ALLOWED,
+            "only GET and HEAD are allowed",
+        );
+    }
+    let Some((login, route)) = route(request.uri().path()) else {
+        return error(StatusCode::NOT_FOUND, "not found");
+    };
+    // the stars have no timestamps without it
+    if route == Route::Stars && !state.config.output.star_history {
+        return error(StatusCode::NOT_FOUND, "not found");
+    }
+    // one spelling of a login, the allowlisted one, for its snapshots and its fetches
+    let login = match login {
+        Some(login) => match state
+            .config
+            .server
+            .users
+            .iter()
+            .find(|u| u.eq_ignore_ascii_case(&login))
+        {
+            Some(user) => Some(user.clone()),
+            None => return error(StatusCode::NOT_FOUND, "not found"),
+        },
+        None => None,
+    };
+    let query = match parse_query(request.uri().query(), &state.config.server) {
+        Ok(query) => query,
+        Err(message) => return error(StatusCode::BAD_REQUEST, &message),
+    };
+    // only the language chart has a compact form
+    if query.compact && route != Route::Panel(Panel::Languages) {
+        return error(
+            StatusCode::BAD_REQUEST,
+            "layout=compact is only available for langs.svg",
+        );
+    }
+    let key = (login, query.period_days);
+    let snapshot = match get_snapshot(state, &key, Some(Utc::now())).await {
+        Ok(snapshot) => snapshot,
+        Err(AppError::UserNotFoundError) => return error(StatusCode::NOT_FOUND, "no such user"),
+        Err(AppError::TooManyRequestsError) => {
+            let mut response = error(StatusCode::TOO_MANY_REQUESTS, "too many requests");
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from_static("60"));
+            return response;
+        }
+        Err(e) => {
+            warn!("failed to fetch {:?}: {:?}", key, e);
+            return error(StatusCode::BAD_GATEWAY, "failed to fetch the statistics");
+        }
+    };
+
+    let (content_type, body) = match route {
+        Route::Stats => match serde_json::to_string(&Envelope::new(
+            snapshot.login,
+            snapshot.from,
+            snapshot.to,
+            graphql::published(&snapshot.stats, state.config.output.starred_at),
+            state.config.hash.clone(),
+        )) {
+            Ok(json) => ("application/json", json),
+            Err(_) => return error(StatusCode::INTERNAL_SERVER_ERROR, "failed to convert"),
+        },
+        Route::Metrics => {
+            let renderer = Renderer::with_config(
+                snapshot.stats,
+                state.language_colors.clone(),
+                state.language_groups.clone(),
+                state.config.clone(),
+                (snapshot.from, snapshot.to),
+            );
+            (metrics::CONTENT_TYPE, metrics::render_metrics(&renderer))
+        }
+        _ => {
+            let mut renderer = Renderer::with_config(
+                snapshot.stats,
+                state.language_colors.clone(),
+                state.language_groups.clone(),
+                state.config.clone(),
+                (snapshot.from, snapshot.to),
+            );
+            let document = match route {
+                Route::Panel(Panel::Languages) if query.compact => {
+                    renderer.render_compact_languages()
+                }
+                Route::Panel(panel) => renderer.render_panel(panel),
+                Route::Stars => renderer.render_star_history(snapshot.from, snapshot.to),
+                _ => renderer.render(),
+            };
+            let document = renderer::apply_color_scheme(document, query.scheme);
+            ("image/svg+xml", document.to_string())
+        }
+    };
+
+    let etag = etag(&body);
+    let cache_control = format!(
+        "public, max-age={}",
+        refresh_interval(&state.config.server).num_seconds()
+    );
+    let builder = Response::builder()
+        .header(ETAG, &etag)
+        .header(CACHE_CONTROL, cache_control);
+    let not_modified = request
+        .headers()
+        .get(IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map_or(false, |v| v.split(',').any(|tag| tag.trim() == etag));
+    let response = if not_modified {
+        builder.status(StatusCode::NOT_MODIFIED).body(Body::empty())
+    } else if request.method() == Method::HEAD {
+        builder
+            .header(CONTENT_TYPE, content_type)
+            .body(Body::empty())
+    } else {
+        builder
+            .header(CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+    };
+    response.unwrap()
+}
+
+///
+/// A strong validator of the body. The hash only has to be stable while the process runs.
+///
+fn etag(body: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+fn error(status: StatusCode, message: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+        .header(CACHE_CONTROL, "no-store")
+        .body(Body::from(format!("{}\n", message)))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_stat;
+
+    fn create_state() -> State {
+        let now = Utc::now();
+        let stat = RepositoryStat {
+            pushed_at: now,
+            stargazer_count: 3,
+            total_commit_count: 10,
+            period_commit_count: 2,
+            ..create_stat("foo")
+        };
+        let snapshot = Snapshot {
+            fetched_at: now,
+            login: "octocat".to_string(),
+            from: now - Duration::days(DEFAULT_PERIOD_DAYS),
+            to: now,
+            stats: vec![stat],
+        };
+        let mut user_snapshot = snapshot.clone();
+        user_snapshot.stats.push(user_snapshot.stats[0].clone());
+        State {
+            // nothing listens there, a fetch fails
+            github: GitHub {
+                graphql_url: "http://127.0.0.1:9/graphql".to_string(),
+                api_url: "http://127.0.0.1:9".to_string(),
+                token: "test-token".to_string(),
+            },
+            language_colors: HashMap::new(),
+            language_groups: HashMap::new(),
+            config: toml::from_str(
+                r#"
+                languages_count = 10
+                ignore_repositories = []
+                ignore_languages = []
+                [language_mapping]
+                [rename_language]
+                [server]
+                users = ["Octocat"]
+                "#,
+            )
+            .unwrap(),
+            snapshots: SyncMutex::new(HashMap::from([
+                (
+                    (None, DEFAULT_PERIOD_DAYS),
+                    Entry {
+                        snapshot: Some(snapshot),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    (Some("Octocat".to_string()), DEFAULT_PERIOD_DAYS),
+                    Entry {
+                        snapshot: Some(user_snapshot),
+                        requested_at: Some(now),
+                        ..Default::default()
+                    },
+                ),
+            ])),
+            fetches: SyncMutex::new(HashMap::new()),
+        }
+    }
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::get(uri).body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let settings = Server::default();
+        assert_eq!(
+            parse_query(None, &settings),
+            Ok(CardQuery {
+                scheme: ColorScheme::Light,
+                compact: false,
+                period_days: 7,
+            })
+        );
+        assert_eq!(
+            parse_query(Some("theme=dark&layout=compact&period=30d&x=1"), &settings),
+            Ok(CardQuery {
+                scheme: ColorScheme::Dark,
+                compact: true,
+                period_days: 30,
+            })
+        );
+        assert!(parse_query(Some("theme=blue"), &settings).is_err());
+        assert!(parse_query(Some("period=30"), &settings).is_err());
+        assert!(parse_query(Some("period=0d"), &settings).is_err());
+        assert!(parse_query(Some("period=366d"), &settings).is_err());
+        assert!(parse_query(Some("period=45d"), &settings).is_err());
+        let settings = Server {
+            max_period_days: 90,
+            ..Default::default()
+        };
+        assert!(parse_query(Some("period=90d"), &settings).is_ok());
+        assert_eq!(
+            parse_query(Some("period=365d"), &settings),
+            Err("period must be one of 7d, 30d, 90d: 365d".to_string())
+        );
+    }
+
+    #[test]
+    fn test_evict() {
+        let state = create_state();
+        let now = Utc::now();
+        state.snapshots.lock().unwrap().insert(
+            (Some("Octocat".to_string()), 30),
+            Entry {
+                requested_at: Some(now - Duration::hours(IDLE_HOURS + 1)),
+                ..Default::default()
+            },
+        );
+        let mut keys = evict(&state, now);
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                (None, DEFAULT_PERIOD_DAYS),
+                (Some("Octocat".to_string()), DEFAULT_PERIOD_DAYS),
+            ]
+        );
+        assert_eq!(state.snapshots.lock().unwrap().len(), 2);
+
+        let mut keys = evict(&state, now + Duration::hours(IDLE_HOURS));
+        keys.sort();
+        assert_eq!(keys, vec![(None, DEFAULT_PERIOD_DAYS)]);
+    }
+
+    #[test]
+    fn test_route() {
+        assert_eq!(route("/card.svg"), Some((None, Route::Card)));
+        assert_eq!(
+            route("/langs.svg"),
+            Some((None, Route::Panel(Panel::Languages)))
+        );
+        assert_eq!(
+            route("/OctoCat/commits.svg"),
+            Some((Some("OctoCat".to_string()), Route::Panel(Panel::Commits)))
+        );
+        assert_eq!(route("/stats.json"), Some((None, Route::Stats)));
+        assert_eq!(
+            route("/octocat/metrics"),
+            Some((Some("octocat".to_string()), Route::Metrics))
+        );
+        assert_eq!(route("/unknown.svg"), None);
+        assert_eq!(route("/card.png"), None);
+        assert_eq!(route("/a/b/card.svg"), None);
+    }
+
+    #[tokio::test]
+    async fn test_handle() {
+        let state = create_state();
+
+        let response = handle(&state, get("/card.svg?theme=dark")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "image/svg+xml");
+        assert_eq!(response.headers()[CACHE_CONTROL], "public, max-age=3600");
+        let etag = response.headers()[ETAG].clone();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("#0d1117"));
+
+        let request = Request::get("/card.svg?theme=dark")
+            .header(IF_NONE_MATCH, etag)
+            .body(Body::empty())
+            .unwrap();
+        let response = handle(&state, request).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        let response = handle(&state, get("/langs.svg?layout=compact")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("languages-compact"));
+
+        for path in ["/card.svg", "/commits.svg", "/stats.json"] {
+            let response = handle(&state, get(&format!("{}?layout=compact", path))).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", path);
+        }
+
+        let response = handle(&state, get("/stats.json")).await;
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let envelope: Envelope = serde_json::from_slice(&body).unwrap();
+        assert_eq!(envelope.login, "octocat");
+        assert_eq!(envelope.repositories.len(), 1);
+
+        let response = handle(&state, get("/metrics")).await;
+        assert_eq!(response.headers()[CONTENT_TYPE], metrics::CONTENT_TYPE);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).ends_with("# EOF\n"));
+
+        let response = handle(&state, get("/card.svg?period=1000d")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers()[CACHE_CONTROL], "no-store");
+
+        let response = handle(&state, get("/favicon.ico")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = handle(&state, get("/stars.svg")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let request = Request::post("/card.svg").body(Body::empty()).unwrap();
+        let response = handle(&state, request).await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+        let response = handle(&state, get("/octocat/stats.json")).await;
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let envelope: Envelope = serde_json::from_slice(&body).unwrap();
+        assert_eq!(envelope.login, "octocat");
+        assert_eq!(envelope.repositories.len(), 2);
+
+        let response = handle(&state, get("/someone/card.svg")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let mut state = create_state();
+        state.config.server.max_fetches_per_minute = 0;
+        // served from the cache under any spelling of the login, without fetching
+        for path in ["/octocat/card.svg", "/OCTOCAT/card.svg", "/card.svg"] {
+            let response = handle(&state, get(path)).await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let response = handle(&state, get("/octocat/card.svg?period=30d")).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "60");
+        assert!(
+            state.snapshots.lock().unwrap()[&(Some("Octocat".to_string()), 30)]
+                .snapshot
+                .is_none()
+        );
+
+        let mut state = create_state();
+        state.config.server.max_fetches_per_minute = 1;
+        let login = Some("Octocat".to_string());
+        let now = Utc::now();
+        assert!(accept_fetch(&state, &login, now));
+        assert!(!accept_fetch(&state, &login, now));
+        // counted per user
+        assert!(accept_fetch(&state, &None, now));
+        assert!(accept_fetch(&state, &login, now + Duration::minutes(1)));
+    }
+}
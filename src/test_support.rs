@@ -0,0 +1,67 @@
+use crate::config::Config;
+use crate::graphql::{RepositoryLanguage, RepositoryStat};
+use crate::renderer::Renderer;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+///
+/// The smallest config, so that tests do not depend on `config.toml`.
+///
+pub fn create_config() -> Config {
+    toml::from_str(
+        r#"
+        languages_count = 10
+        ignore_repositories = []
+        ignore_languages = []
+        [language_mapping]
+        [rename_language]
+        "#,
+    )
+    .unwrap()
+}
+
+///
+/// The time the tests render at, instead of the time they run at.
+///
+pub fn create_now() -> DateTime<Utc> {
+    "2023-08-08T00:00:00Z".parse().unwrap()
+}
+
+///
+/// A renderer of `stats` with `config` for the week up to `create_now`.
+///
+pub fn create_renderer(stats: Vec<RepositoryStat>, config: Config) -> Renderer {
+    let now = create_now();
+    Renderer::with_config(
+        stats,
+        HashMap::new(),
+        HashMap::new(),
+        config,
+        (now - Duration::days(7), now),
+    )
+}
+
+///
+/// A public repository of octocat without stars, commits or languages. Tests set the fields
+/// they care about with the struct update syntax.
+///
+pub fn create_stat(name: &str) -> RepositoryStat {
+    RepositoryStat {
+        id: name.to_string(),
+        name: name.to_string(),
+        name_with_owner: format!("octocat/{}", name),
+        pushed_at: "2023-08-01T00:00:00Z".parse().unwrap(),
+        ..Default::default()
+    }
+}
+
+///
+/// A language of a repository with `size` bytes.
+///
+pub fn create_language(name: &str, color: &str, size: i64) -> RepositoryLanguage {
+    RepositoryLanguage {
+        name: name.to_string(),
+        color: color.to_string(),
+        size,
+    }
+}